@@ -0,0 +1,89 @@
+use bevy::diagnostic::DiagnosticsStore;
+use bevy::prelude::*;
+
+use physics_project::physics::electrons::ElectronChunks;
+use physics_project::structs::{
+    CurrentLoop, Cylinder, CylindricalCathode, DestructionField, ElectrodeImpact, Electron,
+    EmittingFaces, MagneticField, Plate, ParticleAssets, PlateCathode, PreviousPosition,
+    RepulsionSettings, Species, Velocity, Weight,
+};
+
+/// Electrons per 10×10×10 cell, so neighbour search cost per electron does not depend on `n`.
+const ELECTRONS_PER_CELL: f32 = 5.0;
+
+/// Diode-like world with `n` electrons spread in a cube around the origin at a fixed
+/// density, with every electrode and absorber placed just outside the cube.
+///
+/// `Time` is never advanced, so repeated runs do the full amount of work without moving
+/// or destroying anything.
+pub fn diode_world(n: usize) -> World {
+    let side = 10.0 * (n as f32 / ELECTRONS_PER_CELL).cbrt().max(1.0);
+
+    let mut world = World::new();
+    world.insert_resource(Time::<()>::default());
+    world.insert_resource(ElectronChunks::default());
+    world.insert_resource(RepulsionSettings::default());
+    world.insert_resource(DiagnosticsStore::default());
+    world.init_resource::<Assets<Mesh>>();
+    world.init_resource::<Assets<StandardMaterial>>();
+    world.init_resource::<ParticleAssets>();
+    world.init_resource::<Events<ElectrodeImpact>>();
+
+    world.spawn(MagneticField(Vec3::new(0.0, 0.0, 1.0)));
+    world.spawn((
+        Transform::from_rotation(Quat::from_rotation_z(0.5 * std::f32::consts::PI)),
+        CurrentLoop {
+            radius: side,
+            current: 100.0,
+        },
+    ));
+    world.spawn((
+        Transform::from_xyz(side / 2.0 + 5.0, 0.0, 0.0)
+            .with_rotation(Quat::from_rotation_y(0.5 * std::f32::consts::PI)),
+        PlateCathode {
+            e_field: 10.0,
+            emission_rate: 0.0,
+            emission_carry: 0.0,
+            species: Species::Electron,
+            faces: EmittingFaces::Both,
+        },
+        Plate {
+            height: side,
+            width: side,
+            depth: 1.0,
+        },
+        DestructionField { depth: 0.2 },
+    ));
+    world.spawn((
+        Transform::default(),
+        CylindricalCathode {
+            e_field: 10.0,
+            emission_rate: 0.0,
+            emission_carry: 0.0,
+            species: Species::Electron,
+        },
+        Cylinder {
+            inner_radius: side,
+            outer_radius: side + 5.0,
+            height: side,
+        },
+        DestructionField { depth: 0.8 },
+    ));
+
+    world.spawn_batch((0..n).map(move |_| {
+        let position = Vec3::new(
+            (rand::random::<f32>() - 0.5) * side,
+            (rand::random::<f32>() - 0.5) * side,
+            (rand::random::<f32>() - 0.5) * side,
+        );
+        (
+            Transform::from_translation(position),
+            Velocity(Vec3::new(1.0, 2.0, 3.0)),
+            PreviousPosition(position),
+            Weight(1.0),
+            Species::Electron.particle(),
+            Electron,
+        )
+    }));
+    world
+}
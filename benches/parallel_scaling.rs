@@ -0,0 +1,58 @@
+//! Scaling of the parallel per-electron physics systems with the number of worker threads.
+//!
+//! The compute task pool can only be sized once per process, so run the bench once per
+//! thread count and compare the reported throughput:
+//!
+//! ```text
+//! PHYSICS_BENCH_THREADS=1 cargo bench --bench parallel_scaling
+//! PHYSICS_BENCH_THREADS=16 cargo bench --bench parallel_scaling
+//! ```
+
+use bevy::prelude::*;
+use bevy::tasks::{ComputeTaskPool, TaskPoolBuilder};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+mod common;
+
+use physics_project::controls::apply_destruction_field;
+use physics_project::physics::{
+    apply_cylindrical_cathode_electric_field, apply_plate_cathode_electric_field,
+    move_by_magnetic_fields, move_by_velocity,
+};
+
+fn thread_count() -> usize {
+    std::env::var("PHYSICS_BENCH_THREADS")
+        .ok()
+        .and_then(|threads| threads.parse().ok())
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+}
+
+fn parallel_scaling(c: &mut Criterion) {
+    let threads = thread_count();
+    ComputeTaskPool::get_or_init(|| TaskPoolBuilder::new().num_threads(threads).build());
+
+    let mut group = c.benchmark_group(format!("parallel_scaling/{threads}_threads"));
+    group.sample_size(20);
+    for n in [1_000, 10_000, 100_000] {
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::new("per_electron_systems", n), &n, |b, &n| {
+            let mut world = common::diode_world(n);
+            let mut schedule = Schedule::default();
+            schedule.add_systems(
+                (
+                    move_by_velocity,
+                    move_by_magnetic_fields,
+                    apply_plate_cathode_electric_field,
+                    apply_cylindrical_cathode_electric_field,
+                    apply_destruction_field,
+                )
+                    .chain(),
+            );
+            b.iter(|| schedule.run(&mut world));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parallel_scaling);
+criterion_main!(benches);
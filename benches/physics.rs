@@ -0,0 +1,62 @@
+//! Per-system timings of the physics hot paths at 1k, 10k and 100k electrons.
+//!
+//! See the README for saving a baseline and comparing commits against it.
+
+mod common;
+
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use physics_project::controls::apply_destruction_field;
+use physics_project::physics::electrons::{electron_repulsion, update_electron_chunks};
+use physics_project::physics::{apply_plate_cathode_electric_field, move_by_magnetic_fields};
+
+const ELECTRON_COUNTS: [usize; 3] = [1_000, 10_000, 100_000];
+
+/// Times `systems` alone on a fresh [`common::diode_world`] per electron count, after
+/// running `setup` once on that world.
+fn bench_systems<M1, M2>(
+    c: &mut Criterion,
+    name: &str,
+    setup: impl IntoSystemConfigs<M1> + Clone,
+    systems: impl IntoSystemConfigs<M2> + Clone,
+) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(20);
+    for n in ELECTRON_COUNTS {
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            let mut world = common::diode_world(n);
+            let mut setup_schedule = Schedule::new(Setup);
+            setup_schedule.add_systems(setup.clone());
+            setup_schedule.run(&mut world);
+
+            let mut schedule = Schedule::default();
+            schedule.add_systems(systems.clone());
+            b.iter(|| schedule.run(&mut world));
+        });
+    }
+    group.finish();
+}
+
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct Setup;
+
+fn no_setup() {}
+
+fn physics(c: &mut Criterion) {
+    bench_systems(c, "update_electron_chunks", no_setup, update_electron_chunks);
+    bench_systems(c, "electron_repulsion", update_electron_chunks, electron_repulsion);
+    bench_systems(c, "move_by_magnetic_fields", no_setup, move_by_magnetic_fields);
+    bench_systems(
+        c,
+        "apply_plate_cathode_electric_field",
+        no_setup,
+        apply_plate_cathode_electric_field,
+    );
+    bench_systems(c, "apply_destruction_field", no_setup, apply_destruction_field);
+}
+
+criterion_group!(benches, physics);
+criterion_main!(benches);
@@ -0,0 +1,16 @@
+#!/bin/sh
+# Benchmarks the physics systems at <rev> (default: HEAD~1) and at the working tree,
+# printing criterion's change report for every system. Usage: scripts/bench_compare.sh [rev]
+set -e
+
+rev="${1:-HEAD~1}"
+name="$(git rev-parse --short "$rev")"
+root="$(git rev-parse --show-toplevel)"
+worktree="$(mktemp -d)"
+
+git worktree add --detach "$worktree" "$rev" >/dev/null
+trap 'git worktree remove --force "$worktree"' EXIT
+
+# share the target dir so both runs write baselines to the same target/criterion
+(cd "$worktree" && CARGO_TARGET_DIR="$root/target" cargo bench --bench physics -- --save-baseline "$name")
+cd "$root" && cargo bench --bench physics -- --baseline "$name"
@@ -1,6 +1,35 @@
+use bevy::prelude::Color;
+
 pub const SETTINGS_WINDOW_WIDTH: f32 = 180.;
-pub const SETTINGS_WINDOW_HEIGHT: f32 = 180.;
-pub const E_MAX_VALUE: f32 = 10.0;
+pub const SETTINGS_WINDOW_HEIGHT: f32 = 360.;
+pub const E_MAX_VALUE: f32 = 20.0;
+pub const EMISSION_RATE_MAX_VALUE: f32 = 2000.0;
+pub const MAX_ELECTRONS_VALUE: usize = 100000;
+pub const WEIGHT_MAX_VALUE: f32 = 1000000.0;
 pub const B_MAX_VALUE: f32 = 10.0;
+/// Uniform field of the B slider at start and outside the coil scenes.
+pub const B_DEFAULT_VALUE: f32 = 1.0;
+pub const CURRENT_MAX_VALUE: f32 = 500.0;
+pub const DIPOLE_MAX_VALUE: f32 = 100000.0;
 
 pub const CAMERA_SPEED: f32 = 40.0;
+
+pub const SELECTION_HIGHLIGHT: Color = Color::rgb(0.6, 0.6, 0.2);
+
+/// Vacuum permeability in simulation units, scaled so coil currents of a few hundred
+/// give fields comparable to the uniform B slider.
+pub const MU_0: f32 = 0.2;
+
+/// Kinetic energy `v² / 2` of an electron in simulation units, expressed in eV. With it a
+/// cathode voltage of 10 over a gap of 30 accelerates electrons to 30 eV.
+pub const EV_PER_ENERGY_UNIT: f32 = 0.1;
+/// Length of one simulation unit, used to turn gas pressure into a mean free path.
+pub const METRES_PER_UNIT: f32 = 1e-3;
+/// Planck constant times the speed of light in eV·nm, so a photon of wavelength λ nm
+/// carries `PLANCK_EV_NM / λ` eV.
+pub const PLANCK_EV_NM: f32 = 1239.84;
+/// Highest selectable background gas pressure in pascal.
+pub const PRESSURE_MAX_VALUE: f32 = 100.0;
+/// Temperature in K a cathode's heater is sized for when it is switched to thermionic
+/// emission, a usual operating point of tungsten filaments.
+pub const HEATED_CATHODE_TEMPERATURE: f32 = 2300.0;
@@ -1,119 +1,786 @@
 use std::f32::consts::PI;
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy::prelude::*;
+use bevy::utils::HashSet;
+use crate::constants::EV_PER_ENERGY_UNIT;
+use crate::physics::electrons::CELL_SIZE;
+use crate::physics::field_emission::{
+    electrons_per_second, field_in_volts_per_metre, fowler_nordheim_current_density,
+};
+use crate::physics::fields::ElectricSources;
+use crate::physics::{maxwellian_flux_energy, poisson};
+use crate::physics::secondary_emission::{
+    cosine_direction, secondary_count, secondary_energy, secondary_yield,
+};
 use crate::structs::{
-    Electron, MagneticField,
-    Plate, PlateCathode, Cylinder, CylindricalCathode,
-    DestructionField, Velocity
+    CapPolicy, Collector, Cylinder, CylindricalCathode, DestructionField, Electrode,
+    ElectrodeCurrent, ElectrodeImpact, Electron, EmissionSettings, EnergySpectrum, FieldEmitter,
+    FieldRegion, FranckHertzSweep, MacroParticles, MagneticField, Particle, ParticleAssets,
+    Photocathode, Plate, PlateCathode, PopulationCap, PreviousPosition, SecondaryEmission,
+    SpawnTime, Species, SweptRegion, TimeSeries, Tip, Velocity, Weight,
 };
 
+/// Window over which electrode currents are averaged.
+const CURRENT_WINDOW_SECONDS: f32 = 0.25;
+/// Time between two samples of the plotted time series.
+const TIME_SERIES_INTERVAL: f32 = 0.1;
+/// Impacts kept per electrode for its energy spectrum, older ones are dropped.
+const SPECTRUM_MAX_IMPACTS: usize = 20000;
+/// Particles following one in x order that are searched for its nearest merge partner.
+const MERGE_NEIGHBOURS: usize = 16;
+/// Farthest apart two particles may be merged, further pairs would move charge across the
+/// scene.
+const MERGE_DISTANCE: f32 = CELL_SIZE;
+
+
+/// Keeps particles emitted from a surface clear of its destruction field.
+const SURFACE_OFFSET: f32 = 0.1;
+/// Temperature kT in eV of the electrons thermionic cathodes emit. A real cathode sits near
+/// 0.2 eV; the hotter value keeps the emitted cloud visibly spreading.
+const THERMIONIC_TEMPERATURE: f32 = 0.5;
+/// Field emitted electrons tunnel out near the Fermi level and leave with about this
+/// energy in eV.
+const FIELD_EMISSION_ENERGY: f32 = 0.2;
+
+/// Where a particle's last step first entered an absorber.
+struct Hit {
+    absorber: Entity,
+    is_electrode: bool,
+    /// fraction of the step travelled before the hit
+    t: f32,
+    /// surface normal pointing back to where the particle came from
+    normal: Vec3,
+    /// point just off the struck surface
+    surface_point: Vec3,
+}
+
+/// Entry of the local segment `start -> end` into a plate's destruction slab, as the step
+/// fraction with the normal and surface point in the plate's frame.
+fn plate_hit(start: Vec3, end: Vec3, plate: &Plate, depth: f32) -> Option<(f32, Vec3, Vec3)> {
+    let half_size = Vec3::new(plate.width / 2.0, plate.height / 2.0, depth);
+    let step = end - start;
+    let (mut t_near, mut t_far) = (0.0_f32, 1.0_f32);
+    for axis in 0..3 {
+        if step[axis] == 0.0 {
+            if start[axis].abs() > half_size[axis] {
+                return None;
+            }
+            continue;
+        }
+        let t1 = (-half_size[axis] - start[axis]) / step[axis];
+        let t2 = (half_size[axis] - start[axis]) / step[axis];
+        t_near = t_near.max(t1.min(t2));
+        t_far = t_far.min(t1.max(t2));
+        if t_near > t_far {
+            return None;
+        }
+    }
+
+    // the particle came from the side its step started on
+    let side = if start.z >= 0.0 { 1.0 } else { -1.0 };
+    let point = start + step * t_near;
+    let surface = Vec3::new(point.x, point.y, side * (depth + SURFACE_OFFSET));
+    Some((t_near, Vec3::new(0.0, 0.0, side), surface))
+}
+
+/// Entry of the local segment `start -> end` into the wall of a cylinder, between its
+/// radii and within its height, as for [`plate_hit`].
+fn cylinder_hit(start: Vec3, end: Vec3, cylinder: &Cylinder) -> Option<(f32, Vec3, Vec3)> {
+    let step = end - start;
+    let half_height = cylinder.height / 2.0;
+    let inside = |t: f32| {
+        let p = start + step * t;
+        let r2 = p.x * p.x + p.z * p.z;
+        r2 > cylinder.inner_radius * cylinder.inner_radius
+            && r2 < cylinder.outer_radius * cylinder.outer_radius
+            && p.y.abs() <= half_height
+    };
+
+    // the wall can only be entered at the start, through a radius or through an end cap
+    let a = step.x * step.x + step.z * step.z;
+    let b = 2.0 * (start.x * step.x + start.z * step.z);
+    let c = start.x * start.x + start.z * start.z;
+    let mut candidates = vec![(0.0, false)];
+    for radius in [cylinder.inner_radius, cylinder.outer_radius] {
+        let discriminant = b * b - 4.0 * a * (c - radius * radius);
+        if a > 0.0 && discriminant >= 0.0 {
+            let root = discriminant.sqrt();
+            candidates.push(((-b - root) / (2.0 * a), false));
+            candidates.push(((-b + root) / (2.0 * a), false));
+        }
+    }
+    if step.y != 0.0 {
+        candidates.push(((half_height - start.y) / step.y, true));
+        candidates.push(((-half_height - start.y) / step.y, true));
+    }
+    let (t, through_cap) = candidates
+        .into_iter()
+        .filter(|(t, _)| (0.0..=1.0).contains(t) && inside((t + 1e-4).min(1.0)))
+        .min_by(|a, b| a.0.total_cmp(&b.0))?;
+
+    let point = start + step * t;
+    let radial = Vec3::new(point.x, 0.0, point.z).normalize_or_zero();
+    let r = Vec3::new(point.x, 0.0, point.z).length();
+    let (normal, surface) = if through_cap {
+        let side = point.y.signum();
+        (
+            Vec3::new(0.0, side, 0.0),
+            Vec3::new(point.x, side * (half_height + SURFACE_OFFSET), point.z),
+        )
+    } else if r < (cylinder.inner_radius + cylinder.outer_radius) / 2.0 {
+        // particles hitting the inner half came from inside the cylinder
+        let surface = radial * (cylinder.inner_radius - SURFACE_OFFSET);
+        (-radial, surface + Vec3::new(0.0, point.y, 0.0))
+    } else {
+        let surface = radial * (cylinder.outer_radius + SURFACE_OFFSET);
+        (radial, surface + Vec3::new(0.0, point.y, 0.0))
+    };
+    Some((t, normal, surface))
+}
 
+/// Entry of the local segment `start -> end` into the destruction field of a tip, the
+/// capsule of all points within `depth` of the segment joining the centres of both
+/// hemispheres, as for [`plate_hit`].
+fn tip_hit(start: Vec3, end: Vec3, tip: &Tip, depth: f32) -> Option<(f32, Vec3, Vec3)> {
+    let apex = tip.apex_center();
+    let step = end - start;
+    let closest = |p: Vec3| Vec3::new(0.0, p.y.clamp(-apex.y, apex.y), 0.0);
+    // first crossing of `|offset + step t| = depth` from outside
+    let entry = |offset: Vec3, step: Vec3| {
+        let a = step.length_squared();
+        let b = 2.0 * offset.dot(step);
+        let c = offset.length_squared() - depth * depth;
+        let discriminant = b * b - 4.0 * a * c;
+        (a > 0.0 && discriminant >= 0.0).then(|| (-b - discriminant.sqrt()) / (2.0 * a))
+    };
+
+    let t = if (start - closest(start)).length() <= depth {
+        0.0
+    } else {
+        // through the side of the shaft, or through one of the hemispheres beyond it
+        let radial = Vec3::new(1.0, 0.0, 1.0);
+        let shaft =
+            entry(start * radial, step * radial).filter(|t| (start.y + step.y * t).abs() <= apex.y);
+        let caps = [1.0, -1.0].map(|side| {
+            entry(start - apex * side, step).filter(|t| side * (start.y + step.y * t) >= apex.y)
+        });
+        shaft
+            .into_iter()
+            .chain(caps.into_iter().flatten())
+            .filter(|t| (0.0..=1.0).contains(t))
+            .min_by(f32::total_cmp)?
+    };
+
+    let point = start + step * t;
+    let closest = closest(point);
+    let normal = (point - closest).try_normalize().unwrap_or(Vec3::Y);
+    Some((t, normal, closest + normal * (depth + SURFACE_OFFSET)))
+}
+
+/// Removes particles whose last step entered an absorbing electrode or a bounding panel.
+///
+/// The whole step from the previous to the current position is tested, so fast particles
+/// cannot tunnel through thin electrodes, and the first surface crossed wins. Impacts on
+/// electrodes are sent as [`ElectrodeImpact`] events.
+#[allow(clippy::type_complexity)]
 pub fn apply_destruction_field(
+    time: Res<Time>,
+    commands: ParallelCommands,
+    plate_fields: Query<
+        (Entity, &Transform, &DestructionField, &Plate, Option<&Electrode>),
+        Without<Electron>,
+    >,
+    cylindrical_fields: Query<
+        (Entity, &Transform, &DestructionField, &Cylinder, Option<&Electrode>),
+        Without<Electron>,
+    >,
+    tip_fields: Query<
+        (Entity, &Transform, &DestructionField, &Tip, Option<&Electrode>),
+        Without<Electron>,
+    >,
+    electrons: Query<
+        (Entity, &Transform, &PreviousPosition, &Velocity, &Weight, &Particle),
+        With<Electron>,
+    >,
+) {
+    let is_absorbing = |electrode: Option<&Electrode>| electrode.is_none_or(|e| e.absorbing);
+    let dt = time.delta_seconds();
+    let now = time.elapsed_seconds();
+
+    electrons.par_iter().for_each(|(entity, transform, previous, velocity, weight, particle)| {
+        // tests the step in the absorber's frame and maps the hit back to world space
+        let hit = |absorber: Entity,
+                   absorber_transform: &Transform,
+                   electrode: Option<&Electrode>,
+                   test: &dyn Fn(Vec3, Vec3) -> Option<(f32, Vec3, Vec3)>| {
+            let inverse = absorber_transform.rotation.inverse();
+            let start = inverse * (previous.0 - absorber_transform.translation);
+            let end = inverse * (transform.translation - absorber_transform.translation);
+            test(start, end).map(|(t, normal, surface)| Hit {
+                absorber,
+                is_electrode: electrode.is_some(),
+                t,
+                normal: absorber_transform.rotation * normal,
+                surface_point: absorber_transform.translation
+                    + absorber_transform.rotation * surface,
+            })
+        };
+
+        let plates = plate_fields
+            .iter()
+            .filter(|(.., electrode)| is_absorbing(*electrode))
+            .filter_map(|(absorber, absorber_transform, destruction_field, plate, electrode)| {
+                hit(absorber, absorber_transform, electrode, &|start, end| {
+                    plate_hit(start, end, plate, destruction_field.depth)
+                })
+            });
+        let cylinders = cylindrical_fields
+            .iter()
+            .filter(|(.., electrode)| is_absorbing(*electrode))
+            .filter_map(|(absorber, absorber_transform, _, cylinder, electrode)| {
+                hit(absorber, absorber_transform, electrode, &|start, end| {
+                    cylinder_hit(start, end, cylinder)
+                })
+            });
+        let tips = tip_fields
+            .iter()
+            .filter(|(.., electrode)| is_absorbing(*electrode))
+            .filter_map(|(absorber, absorber_transform, destruction_field, tip, electrode)| {
+                hit(absorber, absorber_transform, electrode, &|start, end| {
+                    tip_hit(start, end, tip, destruction_field.depth)
+                })
+            });
+
+        // destroy
+        let Some(hit) = plates.chain(cylinders).chain(tips).min_by(|a, b| a.t.total_cmp(&b.t))
+        else {
+            return;
+        };
+        let speed = velocity.0.length();
+        let impact = ElectrodeImpact {
+            electrode: hit.absorber,
+            position: previous.0.lerp(transform.translation, hit.t),
+            normal: hit.normal,
+            surface_point: hit.surface_point,
+            time: now - (1.0 - hit.t) * dt,
+            velocity: velocity.0,
+            energy: 0.5 * particle.mass * speed * speed * EV_PER_ENERGY_UNIT,
+            particle: *particle,
+            weight: weight.0,
+        };
+        let is_electrode = hit.is_electrode;
+        commands.command_scope(|mut commands| {
+            commands.entity(entity).despawn();
+            if is_electrode {
+                commands.add(move |world: &mut World| {
+                    world.send_event(impact);
+                });
+            }
+        });
+    });
+}
+
+/// Books the charge of every impact on its electrode. Electrodes with
+/// [`SecondaryEmission`] send secondaries back into the volume, which are booked as
+/// charge leaving the electrode.
+pub fn process_electrode_impacts(
+    assets: Res<ParticleAssets>,
     mut commands: Commands,
-    plate_fields: Query<(&Transform, &DestructionField, &Plate), Without<Electron>>,
-    cylindrical_fields: Query<(&Transform, &DestructionField, &Cylinder), Without<Electron>>,
-    electrons: Query<(Entity, &Transform), With<Electron>>,
+    mut impacts: EventReader<ElectrodeImpact>,
+    mut electrodes: Query<(Option<&mut ElectrodeCurrent>, Option<&SecondaryEmission>)>,
 ) {
-    for (plate_transform, destruction_field, plate) in plate_fields.iter() {
-        for (entity, transform) in electrons.iter() {
-            // check if in range
-            let rel_electron_pos = transform.translation - plate_transform.translation;
-            let rel_electron_pos = plate_transform.rotation.inverse() * rel_electron_pos;
-            if rel_electron_pos.x.abs() > plate.width / 2.0
-                || rel_electron_pos.y.abs() > plate.height / 2.0
-                || rel_electron_pos.z.abs() > destruction_field.depth
-            {
-                continue;
+    let electron = Species::Electron.particle();
+    for impact in impacts.read() {
+        let Ok((current, emission)) = electrodes.get_mut(impact.electrode) else {
+            continue;
+        };
+        let secondaries = match emission {
+            Some(emission) if impact.particle == electron => {
+                let speed = impact.velocity.length();
+                let cos_theta = if speed > 0.0 {
+                    impact.velocity.dot(impact.normal) / speed
+                } else {
+                    1.0
+                };
+                secondary_count(secondary_yield(emission, impact.energy, cos_theta))
             }
+            _ => 0,
+        };
+        for _ in 0..secondaries {
+            let speed = (2.0 * secondary_energy() / EV_PER_ENERGY_UNIT).sqrt();
+            commands.spawn(assets.bundle(
+                Species::Electron,
+                impact.surface_point,
+                cosine_direction(impact.normal) * speed,
+                impact.weight,
+                impact.time,
+            ));
+        }
 
-            // destroy
-            commands.entity(entity).despawn();
+        if let Some(mut current) = current {
+            let charge = (-impact.particle.charge - secondaries as f32) * impact.weight;
+            current.collected += charge;
+            current.total += charge;
         }
     }
+}
 
-    for (cylinder_transform, destruction_field, cylinder) in cylindrical_fields.iter() {
-        for (entity, transform) in electrons.iter() {
-            let rel_electron_pos = (
-                    (transform.translation.x - cylinder_transform.translation.x) *
-                        (transform.translation.x - cylinder_transform.translation.x)
-                        + transform.translation.z*transform.translation.z
-            ).sqrt();
-            if rel_electron_pos > cylinder.inner_radius  &&
-                rel_electron_pos < cylinder.outer_radius
-            {
-                commands.entity(entity).despawn();
-            }
+/// Keeps the energies of the latest electrons absorbed by every electrode for the energy
+/// spectrum, forgetting electrodes that are gone.
+pub fn record_impact_energies(
+    mut spectrum: ResMut<EnergySpectrum>,
+    mut impacts: EventReader<ElectrodeImpact>,
+    electrodes: Query<(), With<Electrode>>,
+) {
+    let electron = Species::Electron.particle();
+    for impact in impacts.read().filter(|impact| impact.particle == electron) {
+        let energies = spectrum.impacts.entry(impact.electrode).or_default();
+        if energies.len() >= SPECTRUM_MAX_IMPACTS {
+            energies.pop_front();
         }
+        energies.push_back((impact.energy, impact.weight));
     }
+    spectrum.impacts.retain(|entity, _| electrodes.contains(*entity));
 }
 
+/// Samples electron count and energy, electrode currents, cathode voltages, the applied
+/// magnetic field and the frame time into the plotted time series.
+#[allow(clippy::type_complexity)]
+pub fn record_time_series(
+    time: Res<Time>,
+    mut sample: Local<Option<Timer>>,
+    mut time_series: ResMut<TimeSeries>,
+    diagnostics: Res<DiagnosticsStore>,
+    electrons: Query<(&Velocity, &Particle, &Weight), With<Electron>>,
+    electrodes: Query<(
+        &Electrode,
+        Option<&ElectrodeCurrent>,
+        Option<&PlateCathode>,
+        Option<&CylindricalCathode>,
+    )>,
+    magnetic_fields: Query<&MagneticField>,
+) {
+    let sample = sample
+        .get_or_insert_with(|| Timer::from_seconds(TIME_SERIES_INTERVAL, TimerMode::Repeating));
+    if time_series.paused || !sample.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let now = time.elapsed_seconds_f64();
+    let electron = Species::Electron.particle();
+    let (count, weight, energy) = electrons
+        .iter()
+        .filter(|(_, particle, _)| **particle == electron)
+        .fold((0, 0.0, 0.0), |(count, weight, energy), (velocity, _, w)| {
+            let e = 0.5 * velocity.0.length_squared() * EV_PER_ENERGY_UNIT;
+            (count + 1, weight + w.0, energy + e * w.0)
+        });
+    time_series.push("Electrons", now, count as f64);
+    if weight > 0.0 {
+        time_series.push("Mean energy, eV", now, (energy / weight) as f64);
+    }
+    if let Some(frame_time) = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|d| d.smoothed())
+    {
+        time_series.push("Frame time, ms", now, frame_time);
+    }
+    let b = magnetic_fields.iter().map(|field| field.0).sum::<Vec3>().length();
+    time_series.push("Uniform B", now, b as f64);
+
+    for (electrode, current, plate_cathode, cylindrical_cathode) in electrodes.iter() {
+        if let Some(current) = current {
+            let name = format!("Current {}, e/s", electrode.name);
+            time_series.push(&name, now, current.current as f64);
+        }
+        let voltage = plate_cathode
+            .map(|cathode| cathode.e_field)
+            .or(cylindrical_cathode.map(|cathode| cathode.e_field));
+        if let Some(voltage) = voltage {
+            time_series.push(&format!("Voltage {}", electrode.name), now, voltage as f64);
+        }
+    }
+
+    time_series.prune(now);
+}
+
+/// Turns the charge collected by each electrode into a current.
+pub fn update_electrode_currents(
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    mut electrodes: Query<&mut ElectrodeCurrent>,
+) {
+    *elapsed += time.delta_seconds();
+    if *elapsed < CURRENT_WINDOW_SECONDS {
+        return;
+    }
+    for mut electrode in electrodes.iter_mut() {
+        electrode.current = electrode.collected / *elapsed;
+        electrode.collected = 0.0;
+    }
+    *elapsed = 0.0;
+}
+
+/// Field emission follows the applied field along the tip axis at its apex, this sets the
+/// surface field and emission rate of every field emitter from it.
+pub fn update_field_emitters(
+    sources: ElectricSources,
+    mut field_emitters: Query<(&Transform, &Tip, &mut FieldEmitter)>,
+) {
+    for (transform, tip, mut emitter) in field_emitters.iter_mut() {
+        let axis = transform.rotation * Vec3::Y;
+        let apex = transform.translation + transform.rotation * tip.apex_center();
+        let applied = sources.field_at(apex + axis * tip.radius).dot(axis);
+        emitter.surface_field = field_in_volts_per_metre(emitter.enhancement * applied);
+        let density =
+            fowler_nordheim_current_density(emitter.surface_field, emitter.work_function);
+        emitter.emission_rate = electrons_per_second(density, emitter.area * 1e-18);
+    }
+}
+
+/// Whole number of particles to emit this tick for `expected` on average. Without noise
+/// the fraction left over is carried to the next tick, so the emitted rate is exact and
+/// independent of the tick length.
+fn emission_count(expected: f32, carry: &mut f32, poisson_noise: bool) -> u32 {
+    if poisson_noise {
+        return poisson(expected);
+    }
+    *carry += expected;
+    let count = carry.floor();
+    *carry -= count;
+    count as u32
+}
+
+/// Emits particles from cathodes and field emitters at their rate every tick. Each new
+/// particle is placed as if emitted at a random moment within the tick, so emission
+/// is spread continuously in time instead of arriving in per-tick layers.
+///
+/// Cathodes emit from their surface, plates from their emitting faces and cylinders from
+/// the outer wall, with a Lambertian spread of directions around the outward normal.
+#[allow(clippy::too_many_arguments)]
 pub fn cathodes_spawn_electrons(
     time: Res<Time>,
-    mut spawn_timer: ResMut<crate::structs::SpawnTimer>,
-    plate_cathodes: Query<(&Transform, &PlateCathode, &Plate)>,
-    cylindrical_cathodes:  Query<(&Transform, &CylindricalCathode, &Cylinder)>,
+    settings: Res<EmissionSettings>,
+    mut plate_cathodes: Query<
+        (&Transform, &mut PlateCathode, &Plate, Option<&DestructionField>),
+        Without<Photocathode>,
+    >,
+    mut cylindrical_cathodes: Query<(&Transform, &mut CylindricalCathode, &Cylinder)>,
+    mut field_emitters: Query<(&Transform, &Tip, &mut FieldEmitter, Option<&DestructionField>)>,
+    electrons: Query<(), With<Electron>>,
+    cap: Res<PopulationCap>,
+    macro_particles: Res<MacroParticles>,
+    assets: Res<ParticleAssets>,
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    let mut spawn = |position: Vec3, velocity: Vec3| {
-        let mesh = meshes.add(Sphere::new(1.0).mesh().ico(3).unwrap());
-
-        commands.spawn((
-            PbrBundle {
-                mesh,
-                material: materials.add(Color::rgb(0.0, 0.0, 1.0)),
-                transform: Transform::from_translation(position),
-                ..Default::default()
-            },
-            Electron,
-            Velocity(velocity),
+    let dt = time.delta_seconds();
+    let spawn_time = time.elapsed_seconds();
+    let mut spawn = |species: Species, position: Vec3, velocity: Vec3| {
+        // emitted `lag` seconds ago, somewhere within this tick
+        let lag = rand::random::<f32>() * dt;
+        commands.spawn(assets.bundle(
+            species,
+            position + velocity * lag,
+            velocity,
+            macro_particles.weight,
+            spawn_time - lag,
         ));
     };
 
-    if !spawn_timer.0.tick(time.delta()).just_finished() {
-        return;
-    }
+    let plate_counts = plate_cathodes
+        .iter_mut()
+        .map(|(_, mut cathode, _, _)| {
+            let expected = cathode.emission_rate * dt / macro_particles.weight;
+            emission_count(expected, &mut cathode.emission_carry, settings.poisson)
+        })
+        .collect::<Vec<_>>();
+    let cylinder_counts = cylindrical_cathodes
+        .iter_mut()
+        .map(|(_, mut cathode, _)| {
+            let expected = cathode.emission_rate * dt / macro_particles.weight;
+            emission_count(expected, &mut cathode.emission_carry, settings.poisson)
+        })
+        .collect::<Vec<_>>();
+
+    let tip_emission = field_emitters
+        .iter_mut()
+        .map(|(transform, tip, mut emitter, destruction_field)| {
+            let apex = transform.translation + transform.rotation * tip.apex_center();
+            let expected = (emitter.emission_rate * dt / macro_particles.weight)
+                .min(cap.max_electrons as f32);
+            let count = emission_count(expected, &mut emitter.emission_carry, settings.poisson);
+            // just off the apex and clear of its destruction field
+            let surface = destruction_field.map_or(0.0, |field| field.depth).max(tip.radius);
+            (apex, transform.rotation, surface, count)
+        })
+        .collect::<Vec<_>>();
 
-    for (plate_transform, plate_cathode, plate) in plate_cathodes.iter() {
-        for _ in 0..plate_cathode.emmisivness {
+    // when throttling, every cathode gets the same share of the room left under the cap
+    let requested = plate_counts.iter().sum::<u32>()
+        + cylinder_counts.iter().sum::<u32>()
+        + tip_emission.iter().map(|(_, _, _, count)| count).sum::<u32>();
+    let room = cap.max_electrons.saturating_sub(electrons.iter().count());
+    let emit_probability = match cap.policy {
+        CapPolicy::Throttle if requested > 0 => (room as f32 / requested as f32).min(1.0),
+        _ => 1.0,
+    };
+    let mut spawn = |species: Species, position: Vec3, velocity: Vec3| {
+        if rand::random::<f32>() < emit_probability {
+            spawn(species, position, velocity);
+        }
+    };
+
+    let thermal_speed = |species: Species| {
+        let energy = maxwellian_flux_energy(THERMIONIC_TEMPERATURE);
+        (2.0 * energy / EV_PER_ENERGY_UNIT / species.particle().mass).sqrt()
+    };
+
+    for ((plate_transform, plate_cathode, plate, destruction_field), count) in
+        plate_cathodes.iter().zip(plate_counts)
+    {
+        // just off the plate and clear of its destruction field
+        let surface = destruction_field
+            .map_or(0.0, |field| field.depth)
+            .max(plate.depth / 2.0)
+            + SURFACE_OFFSET;
+        let sides = plate_cathode.faces.sides();
+        for _ in 0..count {
+            let side = sides[rand::random::<usize>() % sides.len()];
             let position = plate_transform.translation
                 + plate_transform.rotation
                     * Vec3::new(
                         (rand::random::<f32>() - 0.5) * plate.width,
                         (rand::random::<f32>() - 0.5) * plate.height,
-                        (rand::random::<f32>() - 0.5) * plate.depth,
+                        side * surface,
                     );
-            let velocity = Vec3::new(
-                (rand::random::<f32>() - 0.5) * 10.0,
-                (rand::random::<f32>() - 0.5) * 10.0,
-                (rand::random::<f32>() - 0.5) * 10.0,
-            );
+            let normal = plate_transform.rotation * Vec3::new(0.0, 0.0, side);
+            let velocity = cosine_direction(normal) * thermal_speed(plate_cathode.species);
 
-            spawn(position, velocity);
+            spawn(plate_cathode.species, position, velocity);
         }
     }
 
-    for (cylinder_transform, cylinder_cathode, cylinder) in cylindrical_cathodes.iter() {
-        for _ in 0..cylinder_cathode.emmisivness {
+    for ((cylinder_transform, cylinder_cathode, cylinder), count) in
+        cylindrical_cathodes.iter().zip(cylinder_counts)
+    {
+        for _ in 0..count {
             let phi = rand::random::<f32>() * 2.0 * PI - PI;
+            let radial = Vec3::new(phi.cos(), 0.0, phi.sin());
             let position = cylinder_transform.translation
                 + cylinder_transform.rotation
+                    * (radial * (cylinder.outer_radius + SURFACE_OFFSET)
+                        + Vec3::new(0.0, (rand::random::<f32>() - 0.5) * cylinder.height, 0.0));
+            let normal = cylinder_transform.rotation * radial;
+            let velocity = cosine_direction(normal) * thermal_speed(cylinder_cathode.species);
+
+            spawn(cylinder_cathode.species, position, velocity);
+        }
+    }
+
+    let speed = (2.0 * FIELD_EMISSION_ENERGY / EV_PER_ENERGY_UNIT).sqrt();
+    for (apex, rotation, surface, count) in tip_emission {
+        for _ in 0..count {
+            let direction = cosine_direction(rotation * Vec3::Y);
+            let position = apex + direction * (surface + SURFACE_OFFSET);
+            spawn(Species::Electron, position, direction * speed);
+        }
+    }
+}
+
+/// Photoelectrons from photon hits on photocathodes, Poisson distributed in time.
+///
+/// Photons below the work function are absorbed without effect; above it every
+/// photoelectron leaves with the kinetic energy hν − φ.
+pub fn photocathode_emission(
+    time: Res<Time>,
+    assets: Res<ParticleAssets>,
+    macro_particles: Res<MacroParticles>,
+    mut commands: Commands,
+    mut photocathodes: Query<(&Transform, &Plate, &DestructionField, &mut Photocathode)>,
+) {
+    let spawn_time = time.elapsed_seconds();
+    for (transform, plate, destruction_field, mut photocathode) in photocathodes.iter_mut() {
+        let photons = poisson(photocathode.photon_rate * time.delta_seconds())
+            + std::mem::take(&mut photocathode.pending_photons);
+        let kinetic_energy = photocathode.kinetic_energy();
+        if kinetic_energy <= 0.0 {
+            continue;
+        }
+        // every photon is converted independently with the quantum efficiency
+        let emitted = (0..photons)
+            .filter(|_| rand::random::<f32>() < photocathode.quantum_efficiency)
+            .count();
+        photocathode.photoelectrons += emitted as f32;
+        // each particle stands for `weight` of the real photoelectrons
+        let particles = (0..emitted)
+            .filter(|_| rand::random::<f32>() * macro_particles.weight < 1.0)
+            .count();
+
+        let normal = transform.rotation * Vec3::Z;
+        let speed = (2.0 * kinetic_energy / EV_PER_ENERGY_UNIT).sqrt();
+        for _ in 0..particles {
+            let position = transform.translation
+                + transform.rotation
                     * Vec3::new(
-                        cylinder.outer_radius * phi.cos(),
-                        (rand::random::<f32>() - 0.5) * cylinder.height,
-                        cylinder.outer_radius * phi.sin(),
+                        (rand::random::<f32>() - 0.5) * plate.width,
+                        (rand::random::<f32>() - 0.5) * plate.height,
+                        destruction_field.depth + SURFACE_OFFSET,
                     );
-            let velocity = Vec3::new(
-                (rand::random::<f32>() - 0.5) * 10.0,
-                (rand::random::<f32>() - 0.5) * 10.0,
-                (rand::random::<f32>() - 0.5) * 10.0,
-            );
+            commands.spawn(assets.bundle(
+                Species::Electron,
+                position,
+                cosine_direction(normal) * speed,
+                macro_particles.weight,
+                spawn_time,
+            ));
+        }
+    }
+}
 
-            spawn(position, velocity);
+/// Keeps the electron count under [`PopulationCap`] by dropping or merging electrons.
+///
+/// Runs after every system that spawns particles, so whatever they added this tick is
+/// counted. The policy gets the first go at the excess: merging pairs up particles of one
+/// species within [`MERGE_DISTANCE`] of each other. What is still over the cap afterwards
+/// is dropped, the newest when throttling, as if they had never been emitted, and the
+/// oldest otherwise.
+#[allow(clippy::type_complexity)]
+pub fn enforce_population_cap(
+    mut commands: Commands,
+    mut cap: ResMut<PopulationCap>,
+    mut electrons: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut Weight,
+            &SpawnTime,
+            &Particle,
+            &mut PreviousPosition,
+        ),
+        With<Electron>,
+    >,
+) {
+    let count = electrons.iter().count();
+    let active = count >= cap.max_electrons;
+    if cap.active != active {
+        cap.active = active;
+    }
+    let excess = count.saturating_sub(cap.max_electrons);
+    if excess == 0 {
+        return;
+    }
+
+    let mut removed = HashSet::new();
+    if cap.policy == CapPolicy::Merge {
+        // each particle proposes the nearest particle of its species within reach among its
+        // neighbours along x, and the closest proposals are merged first
+        let mut particles = electrons
+            .iter()
+            .map(|(entity, transform, .., particle, _)| (entity, transform.translation, *particle))
+            .collect::<Vec<_>>();
+        particles.sort_unstable_by(|a, b| a.1.x.total_cmp(&b.1.x));
+        let mut pairs = particles
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (entity, position, particle))| {
+                particles[i + 1..]
+                    .iter()
+                    .take_while(|(_, other_position, _)| {
+                        other_position.x - position.x <= MERGE_DISTANCE
+                    })
+                    .take(MERGE_NEIGHBOURS)
+                    .filter(|(_, _, other)| other == particle)
+                    .map(|(other, other_position, _)| {
+                        (position.distance_squared(*other_position), *entity, *other)
+                    })
+                    .filter(|(distance_squared, ..)| {
+                        *distance_squared <= MERGE_DISTANCE * MERGE_DISTANCE
+                    })
+                    .min_by(|a, b| a.0.total_cmp(&b.0))
+            })
+            .collect::<Vec<_>>();
+        pairs.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut merged_pairs = HashSet::new();
+        for (_, survivor, merged) in pairs {
+            if removed.len() == excess {
+                break;
+            }
+            if !merged_pairs.insert(survivor) || !merged_pairs.insert(merged) {
+                continue;
+            }
+            let Ok([mut a, b]) = electrons.get_many_mut([survivor, merged]) else {
+                continue;
+            };
+            // one heavier particle keeping the total charge and momentum (kinetic energy is
+            // not conserved)
+            let (wa, wb) = (a.3 .0, b.3 .0);
+            let total = wa + wb;
+            a.1.translation = (a.1.translation * wa + b.1.translation * wb) / total;
+            // the merged particle did not travel to its new position
+            a.6 .0 = a.1.translation;
+            a.2 .0 = (a.2 .0 * wa + b.2 .0 * wb) / total;
+            a.3 .0 = total;
+            commands.entity(merged).despawn();
+            removed.insert(merged);
         }
     }
+
+    let remaining = excess - removed.len();
+    if remaining == 0 {
+        return;
+    }
+    let mut by_age = electrons
+        .iter()
+        .filter(|(entity, ..)| !removed.contains(entity))
+        .map(|(entity, _, _, _, spawn_time, ..)| (spawn_time.0, entity))
+        .collect::<Vec<_>>();
+    by_age.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+    if cap.policy == CapPolicy::Throttle {
+        by_age.reverse();
+    }
+    for (_, entity) in by_age.into_iter().take(remaining) {
+        commands.entity(entity).despawn();
+    }
 }
 
+/// Steps the accelerating voltage of the Franck–Hertz tube and records the collector current.
+pub fn franck_hertz_sweep(
+    time: Res<Time>,
+    mut sweep: ResMut<FranckHertzSweep>,
+    mut regions: Query<&mut FieldRegion, With<SweptRegion>>,
+    collectors: Query<&ElectrodeCurrent, With<Collector>>,
+) {
+    if !sweep.running {
+        return;
+    }
+
+    let collected = collectors.iter().map(|c| c.total).sum::<f32>();
+    sweep.elapsed += time.delta_seconds();
+    if sweep.elapsed >= sweep.dwell / 2.0 && sweep.collected_at.is_none() {
+        sweep.collected_at = Some(collected);
+    }
+    if sweep.elapsed >= sweep.dwell {
+        let averaged = sweep.elapsed - sweep.dwell / 2.0;
+        let start = sweep.collected_at.unwrap_or(collected);
+        let current = (collected - start) / averaged.max(f32::EPSILON);
+        let voltage = sweep.voltage;
+        sweep.points.push([voltage as f64, current as f64]);
+
+        sweep.voltage += sweep.step.max(0.01);
+        sweep.elapsed = 0.0;
+        sweep.collected_at = None;
+        if sweep.voltage > sweep.stop {
+            sweep.running = false;
+            return;
+        }
+    }
+
+    for mut region in regions.iter_mut() {
+        region.voltage = sweep.voltage;
+    }
+}
 
 pub fn update_magnetic_field(
     ui_input: Res<crate::structs::UiState>,
@@ -132,17 +799,341 @@ pub fn update_magnetic_field(
     }
 }
 
-pub fn update_electric_field(
-    ui_input: Res<crate::structs::UiState>,
-    mut plate_cathodes: Query<&mut PlateCathode>,
-    mut cylindrical_cathodes: Query<&mut CylindricalCathode>,
-) {
-    let e_value = ui_input.e_value;
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::math::vec3;
+
+    use super::*;
+    use crate::structs::EmittingFaces;
+
+    /// World holding `positions.len()` electrons spawned one second apart, under a cap.
+    fn capped_world(positions: &[Vec3], max_electrons: usize, policy: CapPolicy) -> World {
+        let mut world = World::new();
+        world.insert_resource(PopulationCap {
+            max_electrons,
+            policy,
+            active: false,
+        });
+        for (i, position) in positions.iter().enumerate() {
+            world.spawn((
+                Electron,
+                Transform::from_translation(*position),
+                PreviousPosition(*position),
+                Velocity(Vec3::X),
+                Weight(1.0),
+                SpawnTime(i as f32),
+                Species::Electron.particle(),
+            ));
+        }
+        world
+    }
+
+    fn spawn_times(world: &mut World) -> Vec<f32> {
+        let mut times = world
+            .query_filtered::<&SpawnTime, With<Electron>>()
+            .iter(world)
+            .map(|spawn_time| spawn_time.0)
+            .collect::<Vec<_>>();
+        times.sort_unstable_by(f32::total_cmp);
+        times
+    }
+
+    #[test]
+    fn throttle_discards_the_newest_over_the_cap() {
+        let positions = (0..10).map(|i| Vec3::X * i as f32).collect::<Vec<_>>();
+        let mut world = capped_world(&positions, 6, CapPolicy::Throttle);
+        world.run_system_once(enforce_population_cap);
+        assert_eq!(spawn_times(&mut world), [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn merge_pairs_the_nearest_and_conserves_charge() {
+        // two close pairs at odd offsets in x order, and lone particles far apart
+        let positions = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(100.0, 0.0, 0.0),
+            Vec3::new(100.5, 0.0, 0.0),
+            Vec3::new(200.0, 0.0, 0.0),
+            Vec3::new(200.0, 0.5, 0.0),
+            Vec3::new(300.0, 0.0, 0.0),
+        ];
+        let mut world = capped_world(&positions, 4, CapPolicy::Merge);
+        world.run_system_once(enforce_population_cap);
+
+        let mut particles = world
+            .query::<(&Transform, &Weight)>()
+            .iter(&world)
+            .map(|(transform, weight)| (transform.translation, weight.0))
+            .collect::<Vec<_>>();
+        particles.sort_unstable_by(|a, b| a.0.x.total_cmp(&b.0.x));
+        assert_eq!(particles.len(), 4);
+        assert_eq!(particles.iter().map(|(_, w)| w).sum::<f32>(), 6.0);
+        assert_eq!(particles[1], (Vec3::new(100.25, 0.0, 0.0), 2.0));
+        assert_eq!(particles[2], (Vec3::new(200.0, 0.25, 0.0), 2.0));
+    }
+
+    #[test]
+    fn merge_leaves_distant_particles_apart() {
+        // one close pair among particles of the same species far apart
+        let positions = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(50.0, 0.0, 0.0),
+            Vec3::new(100.0, 0.0, 0.0),
+            Vec3::new(150.0, 0.0, 0.0),
+            Vec3::new(200.0, 0.0, 0.0),
+            Vec3::new(200.5, 0.0, 0.0),
+        ];
+        let mut world = capped_world(&positions, 3, CapPolicy::Merge);
+        world.run_system_once(enforce_population_cap);
+
+        // the pair merges and the oldest of the rest are dropped
+        let mut particles = world
+            .query::<(&Transform, &Weight)>()
+            .iter(&world)
+            .map(|(transform, weight)| (transform.translation.x, weight.0))
+            .collect::<Vec<_>>();
+        particles.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        assert_eq!(particles, [(100.0, 1.0), (150.0, 1.0), (200.25, 2.0)]);
+    }
+
+    #[test]
+    fn merge_drops_the_oldest_when_nothing_is_close() {
+        // a single particle per species has no partner to merge with
+        let mut world = World::new();
+        world.insert_resource(PopulationCap {
+            max_electrons: 2,
+            policy: CapPolicy::Merge,
+            active: false,
+        });
+        let species = [
+            Species::Electron,
+            Species::Positron,
+            Species::Proton,
+            Species::HeliumIon,
+        ];
+        for (i, species) in species.into_iter().enumerate() {
+            world.spawn((
+                Electron,
+                Transform::default(),
+                PreviousPosition(Vec3::ZERO),
+                Velocity(Vec3::ZERO),
+                Weight(1.0),
+                SpawnTime(i as f32),
+                species.particle(),
+            ));
+        }
+        world.run_system_once(enforce_population_cap);
+        assert_eq!(spawn_times(&mut world), [2.0, 3.0]);
+    }
+
+    /// World with the resources [`cathodes_spawn_electrons`] needs, one second into the run.
+    fn emission_world() -> World {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs(1));
+        world.insert_resource(time);
+        world.init_resource::<EmissionSettings>();
+        world.init_resource::<PopulationCap>();
+        world.init_resource::<MacroParticles>();
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<ParticleAssets>();
+        world
+    }
 
-    for mut cathode in plate_cathodes.iter_mut() {
-        cathode.e_field = e_value;
+    #[test]
+    fn tips_emit_in_the_applied_field() {
+        let mut world = emission_world();
+        world.resource_mut::<PopulationCap>().max_electrons = 100;
+        // 500 V over 20 mm along z, 25 kV/m
+        world.spawn((
+            Transform::default(),
+            FieldRegion {
+                half_extents: Vec3::splat(10.0),
+                voltage: 500.0,
+            },
+        ));
+        let tip = world
+            .spawn((
+                Transform::from_rotation(Quat::from_rotation_x(FRAC_PI_2)),
+                Tip {
+                    length: 4.0,
+                    radius: 0.5,
+                },
+                FieldEmitter::default(),
+            ))
+            .id();
+        world.run_system_once(update_field_emitters);
+        world.run_system_once(cathodes_spawn_electrons);
+
+        let emitter = world.get::<FieldEmitter>(tip).unwrap();
+        let surface_field = emitter.enhancement * 2.5e4;
+        assert!((emitter.surface_field / surface_field - 1.0).abs() < 1e-4);
+        let density = fowler_nordheim_current_density(surface_field, emitter.work_function);
+        let emission_rate = electrons_per_second(density, 1e-18);
+        assert!((emitter.emission_rate / emission_rate - 1.0).abs() < 1e-3);
+        // far above the cap, so a tick fills it
+        assert_eq!(world.query::<&Electron>().iter(&world).count(), 100);
     }
-    for mut cathode in cylindrical_cathodes.iter_mut() {
-        cathode.e_field = e_value;
+
+    fn plate_cathode(species: Species, emission_rate: f32) -> impl Bundle {
+        let cathode = PlateCathode {
+            e_field: 0.0,
+            emission_rate,
+            emission_carry: 0.0,
+            species,
+            faces: EmittingFaces::Front,
+        };
+        let plate = Plate {
+            height: 10.0,
+            width: 10.0,
+            depth: 1.0,
+        };
+        (Transform::default(), cathode, plate)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn ion_cathodes_emit_at_the_thermal_energy() {
+        let mut world = emission_world();
+        world.spawn(plate_cathode(Species::MercuryIon, 2000.0));
+        world.run_system_once(cathodes_spawn_electrons);
+
+        let energies = world
+            .query::<(&Velocity, &Particle)>()
+            .iter(&world)
+            .map(|(velocity, particle)| {
+                0.5 * particle.mass * velocity.0.length_squared() * EV_PER_ENERGY_UNIT
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(energies.len(), 2000);
+        // the flux-weighted Maxwellian averages 2kT whatever the mass
+        let mean = energies.iter().sum::<f32>() / energies.len() as f32;
+        let expected = 2.0 * THERMIONIC_TEMPERATURE;
+        assert!((mean - expected).abs() < 0.1 * expected, "mean energy {mean} eV");
+    }
+
+    fn weights(world: &mut World) -> Vec<f32> {
+        world.query::<&Weight>().iter(world).map(|weight| weight.0).collect()
+    }
+
+    #[test]
+    fn emission_rates_count_real_particles_whatever_the_weight() {
+        let mut world = emission_world();
+        world.resource_mut::<MacroParticles>().weight = 4.0;
+        world.spawn(plate_cathode(Species::Electron, 2000.0));
+        world.run_system_once(cathodes_spawn_electrons);
+        assert_eq!(weights(&mut world), [4.0; 500]);
+
+        let mut world = emission_world();
+        world.resource_mut::<MacroParticles>().weight = 4.0;
+        let photocathode = world
+            .spawn((
+                Transform::default(),
+                Plate {
+                    height: 10.0,
+                    width: 10.0,
+                    depth: 1.0,
+                },
+                DestructionField { depth: 0.5 },
+                Photocathode {
+                    photon_rate: 0.0,
+                    quantum_efficiency: 1.0,
+                    pending_photons: 4000,
+                    ..default()
+                },
+            ))
+            .id();
+        world.run_system_once(photocathode_emission);
+        let photoelectrons = world.get::<Photocathode>(photocathode).unwrap().photoelectrons;
+        assert_eq!(photoelectrons, 4000.0);
+        // a quarter as many particles, drawn at random
+        let emitted = weights(&mut world).iter().sum::<f32>();
+        assert!((emitted / photoelectrons - 1.0).abs() < 0.15, "{emitted} electrons");
+    }
+
+    fn assert_hit(hit: Option<(f32, Vec3, Vec3)>, t: f32, normal: Vec3, surface: Vec3) {
+        let (hit_t, hit_normal, hit_surface) = hit.expect("segment should hit");
+        assert!((hit_t - t).abs() < 1e-4, "t = {hit_t}, expected {t}");
+        assert!(hit_normal.abs_diff_eq(normal, 1e-4), "{hit_normal}");
+        assert!(hit_surface.abs_diff_eq(surface, 1e-4), "{hit_surface}");
+    }
+
+    #[test]
+    fn plate_hits() {
+        let plate = Plate {
+            height: 4.0,
+            width: 4.0,
+            depth: 1.0,
+        };
+        // crossing the slab from the front and from the back
+        let hit = plate_hit(vec3(1.0, 0.0, 2.0), vec3(1.0, 0.0, -2.0), &plate, 0.5);
+        assert_hit(hit, 0.375, Vec3::Z, vec3(1.0, 0.0, 0.6));
+        let hit = plate_hit(vec3(0.0, 1.0, -3.0), vec3(0.0, 1.0, 1.0), &plate, 0.5);
+        assert_hit(hit, 0.625, -Vec3::Z, vec3(0.0, 1.0, -0.6));
+        // a step starting inside the slab is caught at once
+        let hit = plate_hit(vec3(0.0, 0.0, 0.2), vec3(0.0, 0.0, 5.0), &plate, 0.5);
+        assert_hit(hit, 0.0, Vec3::Z, vec3(0.0, 0.0, 0.6));
+        // passing beside the plate or stopping short of it
+        assert!(plate_hit(vec3(3.0, 0.0, 2.0), vec3(3.0, 0.0, -2.0), &plate, 0.5).is_none());
+        assert!(plate_hit(vec3(0.0, 0.0, 3.0), vec3(0.0, 0.0, 1.0), &plate, 0.5).is_none());
+    }
+
+    #[test]
+    fn cylinder_hits() {
+        let cylinder = Cylinder {
+            inner_radius: 5.0,
+            outer_radius: 6.0,
+            height: 10.0,
+        };
+        // the inner wall from the bore, the outer wall from outside
+        let hit = cylinder_hit(Vec3::ZERO, vec3(5.5, 0.0, 0.0), &cylinder);
+        assert_hit(hit, 5.0 / 5.5, -Vec3::X, vec3(4.9, 0.0, 0.0));
+        let hit = cylinder_hit(vec3(0.0, 1.0, 10.0), vec3(0.0, 1.0, 5.5), &cylinder);
+        assert_hit(hit, 4.0 / 4.5, Vec3::Z, vec3(0.0, 1.0, 6.1));
+        // into the wall through an end cap
+        let hit = cylinder_hit(vec3(5.5, 8.0, 0.0), vec3(5.5, 2.0, 0.0), &cylinder);
+        assert_hit(hit, 0.5, Vec3::Y, vec3(5.5, 5.1, 0.0));
+        // starting in the inner half of the wall, so coming from the bore
+        let hit = cylinder_hit(vec3(0.0, 0.0, -5.2), vec3(0.0, 0.0, -7.0), &cylinder);
+        assert_hit(hit, 0.0, Vec3::Z, vec3(0.0, 0.0, -4.9));
+        // along the bore and over the top
+        assert!(cylinder_hit(vec3(1.0, -8.0, 0.0), vec3(1.0, 8.0, 0.0), &cylinder).is_none());
+        assert!(cylinder_hit(vec3(-8.0, 6.0, 0.0), vec3(8.0, 6.0, 0.0), &cylinder).is_none());
+    }
+
+    #[test]
+    fn tip_hits() {
+        let tip = Tip {
+            length: 10.0,
+            radius: 1.0,
+        };
+        // through the side of the shaft
+        let hit = tip_hit(vec3(3.0, 0.0, 0.0), vec3(-3.0, 0.0, 0.0), &tip, 1.0);
+        assert_hit(hit, 2.0 / 6.0, Vec3::X, vec3(1.1, 0.0, 0.0));
+        // onto the apex, head on and off axis
+        let hit = tip_hit(vec3(0.0, 8.0, 0.0), Vec3::ZERO, &tip, 1.0);
+        assert_hit(hit, 3.0 / 8.0, Vec3::Y, vec3(0.0, 5.1, 0.0));
+        let hit = tip_hit(vec3(0.6, 7.0, 0.0), vec3(0.6, 3.0, 0.0), &tip, 1.0);
+        assert_hit(hit, 0.55, vec3(0.6, 0.8, 0.0), vec3(0.66, 4.88, 0.0));
+        // onto the far end
+        let hit = tip_hit(vec3(0.0, -9.0, 0.0), Vec3::ZERO, &tip, 1.0);
+        assert_hit(hit, 4.0 / 9.0, -Vec3::Y, vec3(0.0, -5.1, 0.0));
+        // a step far longer than the tip is thick
+        let hit = tip_hit(vec3(0.0, 2.0, -100.0), vec3(0.0, 2.0, 100.0), &tip, 1.0);
+        assert_hit(hit, 99.0 / 200.0, -Vec3::Z, vec3(0.0, 2.0, -1.1));
+        // starting inside
+        let hit = tip_hit(vec3(0.5, 4.5, 0.0), vec3(3.0, 4.5, 0.0), &tip, 1.0);
+        let normal = vec3(1.0, 1.0, 0.0).normalize();
+        assert_hit(hit, 0.0, normal, vec3(0.0, 4.0, 0.0) + normal * 1.1);
+        // a deeper destruction field reaches further out
+        let hit = tip_hit(vec3(3.0, 0.0, 0.0), vec3(-3.0, 0.0, 0.0), &tip, 1.5);
+        assert_hit(hit, 1.5 / 6.0, Vec3::X, vec3(1.6, 0.0, 0.0));
+        // grazing past the shaft and past the apex
+        assert!(tip_hit(vec3(1.01, -10.0, 0.0), vec3(1.01, 10.0, 0.0), &tip, 1.0).is_none());
+        assert!(tip_hit(vec3(-3.0, 5.2, 0.0), vec3(3.0, 5.2, 0.0), &tip, 1.0).is_none());
+    }
+}
@@ -0,0 +1,9 @@
+#![allow(dead_code)]
+
+pub mod constants;
+pub mod controls;
+pub mod physics;
+pub mod scenes;
+pub mod structs;
+pub mod ui;
+pub mod visualization;
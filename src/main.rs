@@ -1,28 +1,35 @@
-#![allow(dead_code)]
-
-mod constants;
-mod controls;
-mod physics;
-mod scenes;
-mod structs;
-mod ui;
-
-use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
+use bevy::diagnostic::{
+    Diagnostic, FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin, RegisterDiagnostic,
+};
 use bevy::prelude::*;
 use bevy_egui::EguiPlugin;
+use physics_project::{constants, controls, physics, scenes, structs, ui, visualization};
 use controls::{
-    apply_destruction_field, cathodes_spawn_electrons, update_electric_field, update_magnetic_field,
+    apply_destruction_field, cathodes_spawn_electrons, enforce_population_cap,
+    franck_hertz_sweep, photocathode_emission, process_electrode_impacts, record_impact_energies,
+    record_time_series, update_electrode_currents, update_field_emitters, update_magnetic_field,
+};
+use physics::collisions::{gas_collisions, GAS_EXCITATIONS, GAS_IONIZATIONS};
+use physics::electrons::{
+    electron_momentum_diagnostic, electron_repulsion, update_electron_chunks, ElectronChunks,
+    ELECTRON_MOMENTUM, REPULSION_NET_FORCE,
 };
-use physics::electrons::{electron_repulsion, update_electron_chunks, ElectronChunks};
+use physics::thermal::{attach_thermal_models, update_electrode_temperatures};
 use physics::{
     apply_plate_cathode_electric_field, apply_cylindrical_cathode_electric_field,
-    move_by_magnetic_fields, move_by_velocity
+    apply_field_region_electric_field, move_by_magnetic_fields, move_by_velocity
+};
+use structs::{
+    CameraAngles, ElectrodeImpact, EmissionSettings, EnergySpectrum, FranckHertzSweep, GasSettings,
+    MacroParticles, MagnetFieldArrow, MagneticField, ParticleAssets, PopulationCap,
+    PhaseSpaceSettings, RepulsionSettings, SelectedElectrode, ThermalSettings, TimeSeries, UiState,
 };
-use structs::{CameraAngles, MagnetFieldArrow, MagneticField, UiState};
 use ui::{
     camera_controls,
-    change_background_color, change_diode_type,
-    ui_setup, update_magnet_arrow
+    change_background_color, change_diode_type, energy_spectrum_window, fields_window,
+    franck_hertz_window, phase_space_window, plots_window,
+    photomultiplier_window, pick_electrode,
+    ui_setup, update_electrode_materials, update_magnet_arrow
 };
 
 fn main() {
@@ -30,17 +37,30 @@ fn main() {
     app.add_plugins(DefaultPlugins)
         .add_plugins(FrameTimeDiagnosticsPlugin)
         .add_plugins(LogDiagnosticsPlugin::default())
+        .register_diagnostic(Diagnostic::new(ELECTRON_MOMENTUM))
+        .register_diagnostic(Diagnostic::new(REPULSION_NET_FORCE))
+        .register_diagnostic(Diagnostic::new(GAS_IONIZATIONS))
+        .register_diagnostic(Diagnostic::new(GAS_EXCITATIONS))
         .add_plugins(scenes::scenes_plugin)
+        .add_plugins(visualization::visualization_plugin)
         .insert_resource(ClearColor(Color::rgb(255.0, 255.0, 255.0)))
-        .insert_resource(structs::SpawnTimer(Timer::from_seconds(
-            0.1,
-            TimerMode::Repeating,
-        )))
+        .add_event::<ElectrodeImpact>()
         .insert_resource(ElectronChunks::default())
+        .insert_resource(RepulsionSettings::default())
+        .insert_resource(PopulationCap::default())
+        .insert_resource(MacroParticles::default())
+        .insert_resource(EmissionSettings::default())
+        .insert_resource(GasSettings::default())
+        .insert_resource(ThermalSettings::default())
+        .insert_resource(EnergySpectrum::default())
+        .insert_resource(TimeSeries::default())
+        .insert_resource(PhaseSpaceSettings::default())
+        .insert_resource(FranckHertzSweep::default())
+        .init_resource::<ParticleAssets>()
         .insert_resource(Time::<Fixed>::from_hz(500.0))
+        .insert_resource(SelectedElectrode::default())
         .insert_resource(UiState {
-            e_value: 2.0,
-            b_value: 1.0,
+            b_value: constants::B_DEFAULT_VALUE,
             phi_value: 0.0,
             theta_value: 0.0,
             is_window_focused: false,
@@ -56,12 +76,29 @@ fn main() {
                 move_by_magnetic_fields,
                 apply_plate_cathode_electric_field,
                 apply_cylindrical_cathode_electric_field,
+                apply_field_region_electric_field,
+                gas_collisions,
                 apply_destruction_field,
+                process_electrode_impacts.after(apply_destruction_field),
+                update_electrode_currents.after(process_electrode_impacts),
+                update_electrode_temperatures
+                    .after(apply_destruction_field)
+                    .before(cathodes_spawn_electrons),
+                franck_hertz_sweep
+                    .after(update_electrode_currents)
+                    .run_if(in_state(scenes::SelectedScene::FranckHertz)),
+                update_field_emitters.before(cathodes_spawn_electrons),
                 cathodes_spawn_electrons,
+                photocathode_emission,
+                enforce_population_cap
+                    .after(gas_collisions)
+                    .after(process_electrode_impacts)
+                    .after(cathodes_spawn_electrons)
+                    .after(photocathode_emission),
                 update_electron_chunks,
                 electron_repulsion.after(update_electron_chunks),
+                electron_momentum_diagnostic.after(electron_repulsion),
                 update_magnetic_field,
-                update_electric_field,
             ),
         )
         .add_systems(
@@ -69,6 +106,30 @@ fn main() {
             (camera_controls, update_magnet_arrow.after(camera_controls)),
         )
         .add_systems(Update, ui_setup)
+        .add_systems(Update, fields_window.after(ui_setup))
+        .add_systems(Update, (record_impact_energies, energy_spectrum_window.after(ui_setup)))
+        .add_systems(Update, (record_time_series, plots_window.after(ui_setup)))
+        .add_systems(Update, phase_space_window.after(ui_setup))
+        .add_systems(
+            Update,
+            franck_hertz_window
+                .after(ui_setup)
+                .run_if(in_state(scenes::SelectedScene::FranckHertz)),
+        )
+        .add_systems(
+            Update,
+            photomultiplier_window
+                .after(ui_setup)
+                .run_if(in_state(scenes::SelectedScene::Photomultiplier)),
+        )
+        .add_systems(
+            Update,
+            (
+                pick_electrode.after(ui_setup),
+                update_electrode_materials.after(pick_electrode),
+            ),
+        )
+        .add_systems(Update, attach_thermal_models)
         .add_systems(Update, change_background_color)
         .add_systems(Update, change_diode_type);
 
@@ -2,17 +2,71 @@ use bevy::prelude::*;
 
 use crate::structs::{
     Cylinder, CylindricalCathode,
-    Electron, MagneticField,
-    Plate, PlateCathode,
+    Electron, FieldRegion, Particle,
+    Plate, PlateCathode, PreviousPosition,
     Velocity
 };
+use fields::MagneticSources;
 
+pub mod barnes_hut;
+pub mod collisions;
 pub mod electrons;
+pub mod field_emission;
+pub mod fields;
+pub mod secondary_emission;
+pub mod thermal;
 
-pub fn move_by_velocity(time: Res<Time>, mut query: Query<(&Velocity, &mut Transform)>) {
-    for (velocity, mut transform) in query.iter_mut() {
-        transform.translation += velocity.0 * time.delta_seconds();
+pub fn move_by_velocity(
+    time: Res<Time>,
+    mut query: Query<(&Velocity, &mut Transform, &mut PreviousPosition)>,
+) {
+    let dt = time.delta_seconds();
+    query.par_iter_mut().for_each(|(velocity, mut transform, mut previous)| {
+        previous.0 = transform.translation;
+        transform.translation += velocity.0 * dt;
+    });
+}
+
+/// Poisson distributed count with the given mean (Knuth's method, fine for small means).
+pub fn poisson(mean: f32) -> u32 {
+    if mean > 30.0 {
+        // normal approximation, the product below would underflow
+        let u = 1.0 - rand::random::<f32>();
+        let v = rand::random::<f32>();
+        let normal = (-2.0 * u.ln()).sqrt() * (2.0 * std::f32::consts::PI * v).cos();
+        return (mean + mean.sqrt() * normal).round().max(0.0) as u32;
+    }
+    let limit = (-mean).exp();
+    let mut count = 0;
+    let mut product = rand::random::<f32>();
+    while product > limit {
+        count += 1;
+        product *= rand::random::<f32>();
     }
+    count
+}
+
+/// Energy of a particle crossing a surface out of a Maxwellian gas at temperature `kt` in
+/// eV: the flux weighting turns the Maxwellian into a gamma distribution of shape 2.
+pub fn maxwellian_flux_energy(kt: f32) -> f32 {
+    let u = (1.0 - rand::random::<f32>()) * (1.0 - rand::random::<f32>());
+    -kt * u.ln()
+}
+
+/// Weighted RMS emittance `sqrt(<u²><u'²> - <u u'>²)` of `(u, u', weight)` samples, with
+/// the moments taken about the centroid.
+pub fn rms_emittance(samples: &[(f32, f32, f32)]) -> f32 {
+    let total = samples.iter().map(|(_, _, w)| w).sum::<f32>();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    let mean_u = samples.iter().map(|(u, _, w)| u * w).sum::<f32>() / total;
+    let mean_slope = samples.iter().map(|(_, s, w)| s * w).sum::<f32>() / total;
+    let (uu, ss, us) = samples.iter().fold((0.0, 0.0, 0.0), |(uu, ss, us), (u, s, w)| {
+        let (u, s) = (u - mean_u, s - mean_slope);
+        (uu + u * u * w, ss + s * s * w, us + u * s * w)
+    });
+    ((uu * ss - us * us) / (total * total)).max(0.0).sqrt()
 }
 
 pub fn rotate(vec: Vec3, angle_speed_vec: Vec3, time_delta: f32) -> Vec3 {
@@ -26,98 +80,118 @@ pub fn rotate(vec: Vec3, angle_speed_vec: Vec3, time_delta: f32) -> Vec3 {
 
 pub fn move_by_magnetic_fields(
     time: Res<Time>,
-    fields: Query<&MagneticField>,
-    mut electrons: Query<(&mut Transform, &mut Velocity), With<Electron>>,
+    fields: MagneticSources,
+    mut electrons: Query<(&Transform, &mut Velocity, &Particle), With<Electron>>,
 ) {
-    for (mut transform, mut velocity) in electrons.iter_mut() {
-        for field in fields.iter() {
-            let acceleration = velocity.0.cross(field.0);
-
-            // составляющая ортогональная магнитному полю
-            let mut vel_ort =
-                velocity.0 - velocity.0.dot(field.0.normalize()) * field.0.normalize();
-
-            // составляющая параллельная магнитному полю, на нее не влияет сила Лоренца
-            let vel_ = velocity.0 - vel_ort;
-
-            // радиус-вектор движения по дуге (рассматриваем плоскость перпендикулярную магнитному полю)
-            let r = -vel_ort.dot(vel_ort) / acceleration.dot(acceleration) * acceleration;
-
-            // угловая скорость
-            let angle_speed = r.cross(vel_ort) / r.dot(r);
-
-            // перемещение по известным радиус-вектору, угловой скорости и времени
-            // transform.translation += rotate(r, angle_speed, time.delta_seconds()) - r;
-
-            // обновление ортогональной составляющей (тело движется по окружности и меняет свой вектор скорости)
-            vel_ort = rotate(vel_ort, angle_speed, time.delta_seconds());
-
-            // передвижение задаваемое ею
-            // transform.translation += vel_ * time.delta_seconds();
-
-            // возвращаем актуальную скорость
-            velocity.0 = vel_ + vel_ort;
+    let dt = time.delta_seconds();
+    electrons.par_iter_mut().for_each(|(transform, mut velocity, particle)| {
+        // поле в точке, где находится электрон
+        let field = fields.field_at(transform.translation);
+        if field.length_squared() < f32::EPSILON {
+            return;
         }
-    }
+
+        // dv/dt = v x B = -B x v, то есть скорость вращается вокруг -B с угловой скоростью |B|;
+        // составляющая параллельная магнитному полю при этом не меняется.
+        // Для других частиц угловая скорость масштабируется на -q/m
+        velocity.0 = rotate(velocity.0, -field * particle.acceleration_scale(), dt);
+    });
 }
 
 
 pub fn apply_plate_cathode_electric_field(
     time: Res<Time>,
     plate_cathodes: Query<(&Transform, &PlateCathode, &Plate), Without<Electron>>,
-    mut electrons: Query<(&mut Transform, &mut Velocity), With<Electron>>,
+    mut electrons: Query<(&mut Transform, &mut Velocity, &Particle), With<Electron>>,
 ) {
-    for (plate_transform, plate_cathode, plate) in plate_cathodes.iter() {
-        for (mut transform, mut velocity) in electrons.iter_mut() {
-            // check if in range
-            let rel_electron_pos = transform.translation - plate_transform.translation;
-            let rel_electron_pos = plate_transform.rotation.inverse() * rel_electron_pos;
-            if rel_electron_pos.x.abs() > plate.width / 2.0
-                || rel_electron_pos.y.abs() > plate.height / 2.0
-            {
-                continue;
-            }
-
-            // apply force
-            let mut force = Vec3::new(0.0, 0.0, plate_cathode.e_field);
-
-            if rel_electron_pos.z < 0.0 {
-                force *= -1.0;
-            }
-
-            let force = plate_transform.rotation * force;
-
-            // dbg!(force);
-
-            velocity.0 += force * time.delta_seconds();
-            transform.translation += force * time.delta_seconds() * time.delta_seconds() / 2.0;
+    let dt = time.delta_seconds();
+    electrons.par_iter_mut().for_each(|(mut transform, mut velocity, particle)| {
+        for (plate_transform, plate_cathode, plate) in plate_cathodes.iter() {
+            let force =
+                fields::plate_cathode_field(plate_transform, plate_cathode, plate, transform.translation)
+                    * particle.acceleration_scale();
+
+            velocity.0 += force * dt;
+            transform.translation += force * dt * dt / 2.0;
         }
-    }
+    });
 }
 
 pub fn apply_cylindrical_cathode_electric_field(
     time: Res<Time>,
     cylindrical_cathodes: Query<(&Transform, &CylindricalCathode, &Cylinder), Without<Electron>>,
-    mut electrons: Query<(&mut Transform, &mut Velocity), With<Electron>>
+    mut electrons: Query<(&mut Transform, &mut Velocity, &Particle), With<Electron>>
 ){
-
-    for (cylinder_transform, cylindrical_cathode, cylinder) in cylindrical_cathodes.iter() {
-        for (mut transform, mut velocity) in electrons.iter_mut() {
-            let r = (
-                    (transform.translation.x - cylinder_transform.translation.x) *
-                        (transform.translation.x - cylinder_transform.translation.x)
-                        + transform.translation.z*transform.translation.z
-                ).sqrt(); // electron position by radius
-
-            let r2 = cylinder.inner_radius; // radius of anode (big cylinder)
-            let ro = cylindrical_cathode.e_field; // surface charge of the cylinder. Taking it as coefficient from ui.
-            let e_field = ro * (r + r2*r2/r); // resulting value of electric field
+    let dt = time.delta_seconds();
+    electrons.par_iter_mut().for_each(|(mut transform, mut velocity, particle)| {
+        for (cylinder_transform, cylindrical_cathode, cylinder) in cylindrical_cathodes.iter() {
+            let vec_force = fields::cylindrical_cathode_field(
+                cylinder_transform,
+                cylindrical_cathode,
+                cylinder,
+                transform.translation,
+            ) * particle.acceleration_scale();
             // 4.0 * PI, 1.60217663 × 10^(-19) - electron charge, 9.1093837 × 10^(-31) - mass. Not interesting constants :)
 
-            let vec_force = e_field * Vec3::new(transform.translation.x, 0.0, transform.translation.z).normalize();
+            velocity.0 += vec_force * dt;
+            transform.translation += vec_force * dt * dt / 2.0;
+        }
+    });
+}
 
-            velocity.0 += vec_force * time.delta_seconds();
-            transform.translation += vec_force * time.delta_seconds() * time.delta_seconds() / 2.0;
+pub fn apply_field_region_electric_field(
+    time: Res<Time>,
+    regions: Query<(&Transform, &FieldRegion), Without<Electron>>,
+    mut electrons: Query<(&mut Transform, &mut Velocity, &Particle), With<Electron>>,
+) {
+    let dt = time.delta_seconds();
+    electrons.par_iter_mut().for_each(|(mut transform, mut velocity, particle)| {
+        for (region_transform, region) in regions.iter() {
+            let force = fields::field_region_field(region_transform, region, transform.translation)
+                * particle.acceleration_scale();
+
+            velocity.0 += force * dt;
+            transform.translation += force * dt * dt / 2.0;
         }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emittance_of_an_uncorrelated_beam_is_the_product_of_its_spreads() {
+        // u = ±1 and u' = 3 ± 2 in every combination: σu = 1, σu' = 2, no correlation
+        let samples = [(-1.0, 1.0, 1.0), (-1.0, 5.0, 1.0), (1.0, 1.0, 1.0), (1.0, 5.0, 1.0)];
+        assert!((rms_emittance(&samples) - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn emittance_of_a_focused_line_vanishes() {
+        // every slope set by the position, as in a perfectly converging beam
+        let samples = (0..20)
+            .map(|i| {
+                let u = i as f32 * 0.1 - 1.0;
+                (u, -0.5 * u + 0.2, 1.0 + i as f32)
+            })
+            .collect::<Vec<_>>();
+        assert!(rms_emittance(&samples) < 1e-3);
+    }
+
+    #[test]
+    fn emittance_is_weighted() {
+        // a second sample of weight 3 is the same as three copies of it
+        let weighted = [(0.0, 0.0, 1.0), (2.0, 1.0, 3.0), (1.0, 4.0, 2.0)];
+        let copies = [
+            (0.0, 0.0, 1.0),
+            (2.0, 1.0, 1.0),
+            (2.0, 1.0, 1.0),
+            (2.0, 1.0, 1.0),
+            (1.0, 4.0, 1.0),
+            (1.0, 4.0, 1.0),
+        ];
+        assert!((rms_emittance(&weighted) - rms_emittance(&copies)).abs() < 1e-5);
+        assert_eq!(rms_emittance(&[]), 0.0);
     }
 }
@@ -0,0 +1,284 @@
+use bevy::prelude::*;
+
+/// Nodes with at most this many bodies are not split further.
+const LEAF_SIZE: usize = 8;
+/// Guards against endless splitting when many bodies sit on the same spot.
+const MAX_DEPTH: u32 = 20;
+
+/// Octree over point charges for O(n log n) long-range Coulomb sums.
+///
+/// A node far enough away (`size / distance < theta`) is replaced by two point charges:
+/// its positive charge at the centre of positive charge and its negative charge at the
+/// centre of negative charge, so nodes mixing electrons and ions keep their dipole. A node
+/// is never replaced while the point being summed for lies inside it, so a body doesn't
+/// feel its own charge. The error of that approximation grows roughly as `theta²`:
+/// `theta = 0.5` is within about a percent of the direct sum, `theta = 1.0` within about
+/// ten percent.
+pub struct Octree {
+    nodes: Vec<Node>,
+    bodies: Vec<(Vec3, f32)>,
+}
+
+struct Node {
+    center: Vec3,
+    half_size: f32,
+    positive: Pole,
+    negative: Pole,
+    /// index of the first of 8 consecutive children, 0 for leaves
+    first_child: u32,
+    /// bodies of a leaf
+    bodies: std::ops::Range<usize>,
+}
+
+impl Octree {
+    /// Builds the tree from `(position, charge)` pairs.
+    pub fn new(bodies: impl IntoIterator<Item = (Vec3, f32)>) -> Self {
+        let mut bodies = bodies.into_iter().collect::<Vec<_>>();
+        let (min, max) = bodies.iter().fold(
+            (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+            |(min, max), (p, _)| (min.min(*p), max.max(*p)),
+        );
+        let center = (min + max) / 2.0;
+        let half_size = ((max - min).max_element() / 2.0).max(f32::EPSILON);
+
+        let mut tree = Octree {
+            nodes: Vec::new(),
+            bodies: Vec::new(),
+        };
+        if !bodies.is_empty() {
+            tree.nodes.push(Node::leaf(center, half_size, 0..0));
+            tree.build(0, &mut bodies, 0, center, half_size, 0);
+        }
+        tree.bodies = bodies;
+        tree
+    }
+
+    fn build(
+        &mut self,
+        node: usize,
+        bodies: &mut [(Vec3, f32)],
+        offset: usize,
+        center: Vec3,
+        half_size: f32,
+        depth: u32,
+    ) {
+        self.nodes[node].positive = Pole::new(bodies.iter().filter(|(_, q)| *q > 0.0));
+        self.nodes[node].negative = Pole::new(bodies.iter().filter(|(_, q)| *q < 0.0));
+
+        if bodies.len() <= LEAF_SIZE || depth >= MAX_DEPTH {
+            self.nodes[node].bodies = offset..offset + bodies.len();
+            return;
+        }
+
+        let octant = |p: Vec3| {
+            (p.x > center.x) as usize
+                | ((p.y > center.y) as usize) << 1
+                | ((p.z > center.z) as usize) << 2
+        };
+        bodies.sort_unstable_by_key(|(p, _)| octant(*p));
+
+        let first_child = self.nodes.len();
+        self.nodes[node].first_child = first_child as u32;
+        let child_half = half_size / 2.0;
+        for i in 0..8 {
+            self.nodes.push(Node::leaf(
+                child_center(center, child_half, i),
+                child_half,
+                0..0,
+            ));
+        }
+
+        let mut start = 0;
+        for i in 0..8 {
+            let end = start
+                + bodies[start..]
+                    .iter()
+                    .take_while(|(p, _)| octant(*p) == i)
+                    .count();
+            self.build(
+                first_child + i,
+                &mut bodies[start..end],
+                offset + start,
+                child_center(center, child_half, i),
+                child_half,
+                depth + 1,
+            );
+            start = end;
+        }
+    }
+
+    /// Sum of `force(pos - source, charge)` over the tree, approximating far nodes.
+    pub fn sum(&self, pos: Vec3, theta: f32, force: impl Fn(Vec3, f32) -> Vec3) -> Vec3 {
+        let mut total = Vec3::ZERO;
+        if self.nodes.is_empty() {
+            return total;
+        }
+
+        let mut stack = vec![0usize];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if node.first_child == 0 && node.bodies.is_empty() {
+                continue;
+            }
+
+            let poles = [&node.positive, &node.negative];
+            let poles = poles.into_iter().filter(|pole| pole.charge != 0.0);
+            let distance = poles
+                .clone()
+                .map(|pole| pos.distance(pole.center))
+                .fold(f32::INFINITY, f32::min);
+            if node.first_child != 0
+                && !node.contains(pos)
+                && 2.0 * node.half_size < theta * distance
+            {
+                for pole in poles {
+                    total += force(pos - pole.center, pole.charge);
+                }
+            } else if node.first_child != 0 {
+                stack.extend(node.first_child as usize..node.first_child as usize + 8);
+            } else {
+                for (p, q) in &self.bodies[node.bodies.clone()] {
+                    total += force(pos - *p, *q);
+                }
+            }
+        }
+        total
+    }
+}
+
+/// Total charge of one sign in a node, placed at its centre of charge.
+#[derive(Default)]
+struct Pole {
+    charge: f32,
+    center: Vec3,
+}
+
+impl Pole {
+    fn new<'a>(bodies: impl Iterator<Item = &'a (Vec3, f32)>) -> Self {
+        let (charge, moment) = bodies.fold((0.0, Vec3::ZERO), |(charge, moment), (p, q)| {
+            (charge + q, moment + *p * *q)
+        });
+        let center = if charge != 0.0 {
+            moment / charge
+        } else {
+            Vec3::ZERO
+        };
+        Pole { charge, center }
+    }
+}
+
+/// Centre of octant `i` of a node, bit 0 selecting +x, bit 1 +y and bit 2 +z.
+fn child_center(center: Vec3, child_half: f32, i: usize) -> Vec3 {
+    center
+        + Vec3::new(
+            if i & 1 != 0 { child_half } else { -child_half },
+            if i & 2 != 0 { child_half } else { -child_half },
+            if i & 4 != 0 { child_half } else { -child_half },
+        )
+}
+
+impl Node {
+    fn leaf(center: Vec3, half_size: f32, bodies: std::ops::Range<usize>) -> Self {
+        Node {
+            center,
+            half_size,
+            positive: Pole::default(),
+            negative: Pole::default(),
+            first_child: 0,
+            bodies,
+        }
+    }
+
+    fn contains(&self, pos: Vec3) -> bool {
+        (pos - self.center)
+            .abs()
+            .cmple(Vec3::splat(self.half_size))
+            .all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    /// Softened Coulomb force, which vanishes for a body acting on itself.
+    fn coulomb(rel_pos: Vec3, charge: f32) -> Vec3 {
+        charge * rel_pos / (rel_pos.length_squared() + 1e-2).powf(1.5)
+    }
+
+    fn direct_sum(bodies: &[(Vec3, f32)], pos: Vec3) -> Vec3 {
+        bodies.iter().map(|(p, q)| coulomb(pos - *p, *q)).sum()
+    }
+
+    fn random_bodies(count: usize, charge: impl Fn(&mut StdRng) -> f32) -> Vec<(Vec3, f32)> {
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..count)
+            .map(|_| {
+                let p = Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 10.0 - 5.0;
+                (p, charge(&mut rng))
+            })
+            .collect()
+    }
+
+    fn relative_error(approx: Vec3, exact: Vec3) -> f32 {
+        (approx - exact).length() / exact.length()
+    }
+
+    #[test]
+    fn theta_zero_is_the_direct_sum() {
+        let bodies = random_bodies(500, |_| -1.0);
+        let tree = Octree::new(bodies.iter().copied());
+        for pos in [Vec3::new(0.3, -1.2, 2.0), Vec3::splat(20.0)] {
+            let exact = direct_sum(&bodies, pos);
+            assert!(relative_error(tree.sum(pos, 0.0, coulomb), exact) < 1e-4);
+        }
+    }
+
+    #[test]
+    fn approximates_the_direct_sum_at_the_bodies() {
+        let bodies = random_bodies(2000, |_| -1.0);
+        let tree = Octree::new(bodies.iter().copied());
+        // every body lies inside the nodes on its path from the root
+        for (pos, _) in bodies.iter().step_by(50) {
+            let exact = direct_sum(&bodies, *pos);
+            let approx = tree.sum(*pos, 0.7, coulomb);
+            assert!(relative_error(approx, exact) < 0.05, "{approx} vs {exact}");
+        }
+    }
+
+    #[test]
+    fn never_approximates_a_node_holding_the_point() {
+        // the +x+y+z child of the root spans 0..10 and holds a cluster in its far corner and
+        // a body in its near corner, seen from which the child looks small enough
+        let pos = Vec3::splat(0.01);
+        let mut bodies = vec![(Vec3::splat(-10.0), -1.0), (pos, -1.0)];
+        bodies.extend((0..9).map(|i| (Vec3::splat(9.9) - 0.01 * i as f32, -1.0)));
+        let tree = Octree::new(bodies.iter().copied());
+
+        let exact = direct_sum(&bodies, pos);
+        let approx = tree.sum(pos, 0.7, coulomb);
+        assert!(relative_error(approx, exact) < 0.05, "{approx} vs {exact}");
+    }
+
+    #[test]
+    fn keeps_the_dipole_of_mixed_charges() {
+        // neutral pairs of an electron and an ion: a monopole at a centre weighted by |q|
+        // would see no field at all
+        let mut bodies = random_bodies(1000, |_| -1.0);
+        let ions = bodies
+            .iter()
+            .map(|(p, _)| (*p + Vec3::new(0.3, 0.0, 0.0), 1.0))
+            .collect::<Vec<_>>();
+        bodies.extend(ions);
+        let tree = Octree::new(bodies.iter().copied());
+
+        for pos in [Vec3::new(12.0, 3.0, -1.0), Vec3::new(-4.0, 20.0, 6.0)] {
+            let exact = direct_sum(&bodies, pos);
+            let approx = tree.sum(pos, 0.7, coulomb);
+            assert!(relative_error(approx, exact) < 0.05, "{approx} vs {exact}");
+        }
+    }
+}
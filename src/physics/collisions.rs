@@ -0,0 +1,277 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use bevy::diagnostic::{DiagnosticPath, Diagnostics};
+use bevy::prelude::*;
+
+use crate::constants::{EV_PER_ENERGY_UNIT, METRES_PER_UNIT};
+use crate::structs::{
+    Electron, Gas, GasSettings, Particle, ParticleAssets, Species, Velocity, Weight,
+};
+
+const BOLTZMANN: f32 = 1.380649e-23;
+const GAS_TEMPERATURE: f32 = 300.0;
+/// Cross section tables are given in units of 10⁻²⁰ m².
+const CROSS_SECTION_UNIT: f32 = 1e-20;
+
+/// Ionizing collisions per tick.
+pub const GAS_IONIZATIONS: DiagnosticPath = DiagnosticPath::const_new("gas/ionizations");
+/// Exciting collisions per tick.
+pub const GAS_EXCITATIONS: DiagnosticPath = DiagnosticPath::const_new("gas/excitations");
+
+/// Electron impact cross sections of a gas as `(energy in eV, cross section)` points,
+/// interpolated linearly and held constant past the last point.
+pub struct CrossSections {
+    pub elastic: &'static [(f32, f32)],
+    pub excitation_threshold: f32,
+    pub excitation: &'static [(f32, f32)],
+    pub ionization_threshold: f32,
+    pub ionization: &'static [(f32, f32)],
+}
+
+// Approximate values after the usual swarm data sets, with all excited states lumped
+// into a single level at the lowest threshold.
+const HELIUM: CrossSections = CrossSections {
+    elastic: &[
+        (0.0, 5.0), (1.0, 6.0), (5.0, 6.2), (10.0, 5.3), (20.0, 4.0), (50.0, 2.3), (100.0, 1.2),
+        (1000.0, 0.2),
+    ],
+    excitation_threshold: 19.82,
+    excitation: &[
+        (19.82, 0.0), (20.5, 0.05), (25.0, 0.08), (30.0, 0.1), (50.0, 0.08), (100.0, 0.05),
+        (1000.0, 0.01),
+    ],
+    ionization_threshold: 24.59,
+    ionization: &[
+        (24.59, 0.0), (30.0, 0.13), (50.0, 0.28), (100.0, 0.37), (200.0, 0.33), (500.0, 0.22),
+        (1000.0, 0.14),
+    ],
+};
+
+const NEON: CrossSections = CrossSections {
+    elastic: &[
+        (0.0, 1.5), (1.0, 1.6), (5.0, 2.0), (10.0, 2.2), (20.0, 2.0), (50.0, 1.6), (100.0, 1.2),
+        (1000.0, 0.3),
+    ],
+    excitation_threshold: 16.62,
+    excitation: &[
+        (16.62, 0.0), (18.0, 0.02), (20.0, 0.04), (30.0, 0.1), (50.0, 0.12), (100.0, 0.1),
+        (1000.0, 0.03),
+    ],
+    ionization_threshold: 21.56,
+    ionization: &[
+        (21.56, 0.0), (30.0, 0.25), (50.0, 0.55), (100.0, 0.75), (200.0, 0.7), (500.0, 0.5),
+        (1000.0, 0.35),
+    ],
+};
+
+const ARGON: CrossSections = CrossSections {
+    elastic: &[
+        (0.0, 7.5), (0.23, 0.08), (1.0, 1.4), (5.0, 6.0), (10.0, 15.0), (15.0, 17.0), (20.0, 12.0),
+        (50.0, 8.0), (100.0, 5.0), (1000.0, 1.0),
+    ],
+    excitation_threshold: 11.55,
+    excitation: &[
+        (11.55, 0.0), (13.0, 0.1), (15.0, 0.25), (20.0, 0.35), (30.0, 0.35), (50.0, 0.3),
+        (100.0, 0.2), (1000.0, 0.05),
+    ],
+    ionization_threshold: 15.76,
+    ionization: &[
+        (15.76, 0.0), (20.0, 0.5), (30.0, 1.8), (50.0, 2.6), (100.0, 2.85), (200.0, 2.5),
+        (500.0, 1.7), (1000.0, 1.1),
+    ],
+};
+
+const MERCURY: CrossSections = CrossSections {
+    elastic: &[
+        (0.0, 30.0), (0.5, 40.0), (1.0, 45.0), (3.0, 35.0), (5.0, 30.0), (10.0, 25.0), (20.0, 20.0),
+        (50.0, 12.0), (100.0, 8.0), (1000.0, 2.0),
+    ],
+    excitation_threshold: 4.89,
+    excitation: &[
+        (4.89, 0.0), (5.5, 2.0), (6.0, 2.5), (8.0, 1.8), (10.0, 1.2), (20.0, 0.6), (100.0, 0.2),
+        (1000.0, 0.05),
+    ],
+    ionization_threshold: 10.44,
+    ionization: &[
+        (10.44, 0.0), (15.0, 2.5), (20.0, 4.5), (50.0, 6.5), (100.0, 6.0), (500.0, 3.5),
+        (1000.0, 2.2),
+    ],
+};
+
+pub fn cross_sections(gas: Gas) -> &'static CrossSections {
+    match gas {
+        Gas::Helium => &HELIUM,
+        Gas::Neon => &NEON,
+        Gas::Argon => &ARGON,
+        Gas::Mercury => &MERCURY,
+    }
+}
+
+/// Linear interpolation in a `(energy, value)` table, zero below its first point.
+fn interpolate(table: &[(f32, f32)], energy: f32) -> f32 {
+    match table.iter().position(|(e, _)| *e > energy) {
+        Some(0) => 0.0,
+        Some(i) => {
+            let (e0, s0) = table[i - 1];
+            let (e1, s1) = table[i];
+            s0 + (s1 - s0) * (energy - e0) / (e1 - e0)
+        }
+        None => table.last().map_or(0.0, |(_, s)| *s),
+    }
+}
+
+/// Uniformly distributed unit vector.
+pub fn random_direction() -> Vec3 {
+    let cos_theta = 2.0 * rand::random::<f32>() - 1.0;
+    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+    let phi = 2.0 * std::f32::consts::PI * rand::random::<f32>();
+    Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+}
+
+/// Gas molecules per cubic simulation unit.
+fn number_density(pressure: f32) -> f32 {
+    pressure / (BOLTZMANN * GAS_TEMPERATURE) * METRES_PER_UNIT.powi(3)
+}
+
+/// Chance of a collision within `dt` for a particle at `speed` in a gas of `density`
+/// molecules per cubic unit with total cross section `cross_section` in square units.
+fn collision_probability(density: f32, cross_section: f32, speed: f32, dt: f32) -> f32 {
+    // exp_m1 keeps the precision of the tiny per-tick probabilities of a thin gas
+    -(-density * cross_section * speed * dt).exp_m1()
+}
+
+/// Electron collisions with the background gas, Monte Carlo sampled every tick.
+///
+/// An electron travelling `|v| dt` collides with probability `1 - exp(-n σ(E) |v| dt)`,
+/// where `σ` is the sum of the elastic, excitation and ionization cross sections at its
+/// energy; the process is then picked in proportion to its cross section. Elastic
+/// collisions scatter isotropically losing the `2m/M` recoil fraction, excitations lose
+/// the threshold energy, and ionizations also spawn an ion at rest and a secondary
+/// electron sharing the remaining energy at random.
+pub fn gas_collisions(
+    time: Res<Time>,
+    gas: Res<GasSettings>,
+    assets: Res<ParticleAssets>,
+    commands: ParallelCommands,
+    mut electrons: Query<(&Transform, &mut Velocity, &Particle, &Weight), With<Electron>>,
+    mut diagnostics: Diagnostics,
+) {
+    if !gas.enabled {
+        return;
+    }
+
+    let dt = time.delta_seconds();
+    let spawn_time = time.elapsed_seconds();
+    let data = cross_sections(gas.gas);
+    let density = number_density(gas.pressure);
+    // one cross section table unit in square simulation units
+    let area = CROSS_SECTION_UNIT / (METRES_PER_UNIT * METRES_PER_UNIT);
+    let ion = gas.gas.ion();
+    let gas_mass = ion.particle().mass;
+    let electron = Species::Electron.particle();
+    let ionizations = AtomicU32::new(0);
+    let excitations = AtomicU32::new(0);
+
+    electrons
+        .par_iter_mut()
+        .for_each(|(transform, mut velocity, particle, weight)| {
+            if *particle != electron {
+                return;
+            }
+            let speed = velocity.0.length();
+            let energy = 0.5 * speed * speed * EV_PER_ENERGY_UNIT;
+
+            let elastic = interpolate(data.elastic, energy);
+            let excitation = interpolate(data.excitation, energy);
+            let ionization = interpolate(data.ionization, energy);
+            let total = elastic + excitation + ionization;
+            let probability = collision_probability(density, total * area, speed, dt);
+            if total <= 0.0 || rand::random::<f32>() >= probability {
+                return;
+            }
+
+            let speed_for = |energy: f32| (2.0 * energy.max(0.0) / EV_PER_ENERGY_UNIT).sqrt();
+            let pick = rand::random::<f32>() * total;
+            if pick < elastic {
+                let energy = energy * (1.0 - 2.0 / gas_mass);
+                velocity.0 = random_direction() * speed_for(energy);
+            } else if pick < elastic + excitation {
+                excitations.fetch_add(1, Ordering::Relaxed);
+                velocity.0 = random_direction() * speed_for(energy - data.excitation_threshold);
+            } else {
+                ionizations.fetch_add(1, Ordering::Relaxed);
+                let remaining = (energy - data.ionization_threshold).max(0.0);
+                let share = rand::random::<f32>();
+                velocity.0 = random_direction() * speed_for(remaining * (1.0 - share));
+
+                let position = transform.translation;
+                let secondary = random_direction() * speed_for(remaining * share);
+                let weight = weight.0;
+                commands.command_scope(|mut commands| {
+                    commands.spawn(assets.bundle(
+                        Species::Electron,
+                        position,
+                        secondary,
+                        weight,
+                        spawn_time,
+                    ));
+                    commands.spawn(assets.bundle(ion, position, Vec3::ZERO, weight, spawn_time));
+                });
+            }
+        });
+
+    diagnostics.add_measurement(&GAS_IONIZATIONS, || {
+        ionizations.load(Ordering::Relaxed) as f64
+    });
+    diagnostics.add_measurement(&GAS_EXCITATIONS, || {
+        excitations.load(Ordering::Relaxed) as f64
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &[(f32, f32)] = &[(10.0, 0.0), (20.0, 4.0), (40.0, 2.0)];
+
+    #[test]
+    fn interpolation_between_points() {
+        assert_eq!(interpolate(TABLE, 10.0), 0.0);
+        assert!((interpolate(TABLE, 15.0) - 2.0).abs() < 1e-6);
+        assert!((interpolate(TABLE, 20.0) - 4.0).abs() < 1e-6);
+        assert!((interpolate(TABLE, 35.0) - 2.5).abs() < 1e-6);
+        // held at the last point beyond the table
+        assert_eq!(interpolate(TABLE, 1000.0), 2.0);
+    }
+
+    #[test]
+    fn no_inelastic_collisions_below_threshold() {
+        assert_eq!(interpolate(TABLE, 0.0), 0.0);
+        assert_eq!(interpolate(TABLE, 9.99), 0.0);
+        for gas in Gas::ALL {
+            let data = cross_sections(gas);
+            let below = data.excitation_threshold * 0.99;
+            assert_eq!(interpolate(data.excitation, below), 0.0, "{}", gas.name());
+            let below = data.ionization_threshold * 0.99;
+            assert_eq!(interpolate(data.ionization, below), 0.0, "{}", gas.name());
+            assert!(interpolate(data.elastic, 0.0) > 0.0, "{}", gas.name());
+        }
+    }
+
+    #[test]
+    fn collision_probability_is_n_sigma_v_dt_when_small() {
+        // 1 Pa at room temperature, 2.4e20 molecules per m³
+        let density = number_density(1.0);
+        assert!((density / 2.4143e11 - 1.0).abs() < 1e-3, "{density} per mm³");
+
+        let cross_section = 1e-20 / (METRES_PER_UNIT * METRES_PER_UNIT);
+        for (speed, dt) in [(10.0, 0.002), (50.0, 0.01), (200.0, 0.002)] {
+            let expected = density * cross_section * speed * dt;
+            let probability = collision_probability(density, cross_section, speed, dt);
+            assert!((probability / expected - 1.0).abs() < 1e-3, "{probability} vs {expected}");
+        }
+        // a thick gas saturates at certain collision rather than exceeding it
+        let probability = collision_probability(density, cross_section, 1e6, 1.0);
+        assert!(probability <= 1.0 && probability > 0.999);
+    }
+}
@@ -1,17 +1,80 @@
-use bevy::{prelude::*, utils::HashMap};
+use std::ops::Range;
 
-use crate::structs::{Electron, Velocity};
+use bevy::diagnostic::{DiagnosticPath, Diagnostics};
+use bevy::prelude::*;
+use bevy::tasks::{ComputeTaskPool, ParallelSlice, TaskPool};
 
-const CELL_SIZE: f32 = 10.0;
+use crate::physics::barnes_hut::Octree;
+use crate::structs::{Electron, RepulsionMethod, RepulsionSettings, Particle, Velocity, Weight};
+
+/// Side of a neighbour search cell, also the cutoff radius of the cell list method.
+pub const CELL_SIZE: f32 = 10.0;
 
 const ELECTRON_REPULSION_FORCE: f32 = 100.0;
 
+/// Electrons handed to one task when forces are computed in parallel.
+const PARALLEL_BATCH_SIZE: usize = 256;
+
+/// Magnitude of the total momentum of all particles, each counted by its weight.
+pub const ELECTRON_MOMENTUM: DiagnosticPath = DiagnosticPath::const_new("electrons/momentum");
+/// Magnitude of the sum of all repulsion forces in a tick, zero when every pair acts symmetrically.
+pub const REPULSION_NET_FORCE: DiagnosticPath =
+    DiagnosticPath::const_new("electrons/repulsion_net_force");
+
+/// Electrons sorted by the cell they are in (a sorted cell list).
 #[derive(Resource, Default)]
-pub struct ElectronChunks(HashMap<IVec3, Vec<ElectronRepr>>);
+pub struct ElectronChunks {
+    electrons: Vec<ElectronRepr>,
+    /// occupied cells in sorted order and their run in `electrons`
+    cells: Vec<(IVec3, Range<usize>)>,
+}
 
 pub struct ElectronRepr {
     pub position: Vec3,
     pub id: Entity,
+    pub cell: IVec3,
+    pub weight: f32,
+    pub charge: f32,
+    pub mass: f32,
+}
+
+impl ElectronChunks {
+    pub fn electrons(&self) -> &[ElectronRepr] {
+        &self.electrons
+    }
+
+    fn cell_range(&self, cell: IVec3) -> Option<Range<usize>> {
+        self.cells
+            .binary_search_by_key(&cell_key(cell), |(c, _)| cell_key(*c))
+            .ok()
+            .map(|i| self.cells[i].1.clone())
+    }
+
+    fn cell(&self, cell: IVec3) -> &[ElectronRepr] {
+        match self.cell_range(cell) {
+            Some(range) => &self.electrons[range],
+            None => &[],
+        }
+    }
+
+    /// Electrons in `cell` and its 26 neighbours.
+    pub fn neighbourhood(&self, cell: IVec3) -> impl Iterator<Item = &ElectronRepr> {
+        (-1..=1)
+            .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| IVec3::new(x, y, z))))
+            .flat_map(move |offset| self.cell(cell + offset))
+    }
+}
+
+fn cell_key(cell: IVec3) -> (i32, i32, i32) {
+    (cell.x, cell.y, cell.z)
+}
+
+/// The 13 neighbour offsets ordered after the cell itself, so every pair of cells is
+/// visited from exactly one side.
+fn half_shell() -> impl Iterator<Item = IVec3> {
+    (-1..=1)
+        .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| IVec3::new(x, y, z))))
+        .filter(|offset| cell_key(*offset) > (0, 0, 0))
 }
 
 pub fn world_pos_to_chunk_pos(pos: Vec3) -> IVec3 {
@@ -22,54 +85,155 @@ pub fn world_pos_to_chunk_pos(pos: Vec3) -> IVec3 {
     }
 }
 
+/// Softened Coulomb repulsion, `rel_pos` points from the source to the electron.
+///
+/// The softening length `softening` removes the 1/r² singularity at tiny separations.
+pub fn repulsion_force(rel_pos: Vec3, charge: f32, softening: f32) -> Vec3 {
+    let r2 = rel_pos.length_squared() + softening * softening;
+    if r2 == 0.0 {
+        return Vec3::ZERO;
+    }
+    ELECTRON_REPULSION_FORCE * charge * rel_pos / (r2 * r2.sqrt())
+}
+
 pub fn update_electron_chunks(
     mut chunks: ResMut<ElectronChunks>,
-    electrons: Query<(Entity, &Transform), With<Electron>>,
+    electrons: Query<(Entity, &Transform, &Weight, &Particle), With<Electron>>,
 ) {
-    let chunks = &mut chunks.0;
-    chunks.clear();
+    let ElectronChunks { electrons: sorted, cells } = &mut *chunks;
+    sorted.clear();
+    cells.clear();
 
-    for (id, transform) in electrons.iter() {
-        let pos = world_pos_to_chunk_pos(transform.translation);
+    sorted.extend(electrons.iter().map(|(id, transform, weight, particle)| ElectronRepr {
+        position: transform.translation,
+        id,
+        cell: world_pos_to_chunk_pos(transform.translation),
+        weight: weight.0,
+        charge: particle.charge,
+        mass: particle.mass,
+    }));
+    sorted.sort_unstable_by_key(|e| cell_key(e.cell));
 
-        chunks.entry(pos).or_default().push(ElectronRepr {
-            position: transform.translation,
-            id,
-        });
+    let mut start = 0;
+    for i in 1..=sorted.len() {
+        if i == sorted.len() || sorted[i].cell != sorted[start].cell {
+            cells.push((sorted[start].cell, start..i));
+            start = i;
+        }
     }
 }
 
+/// Mutual Coulomb interaction of all particles, repulsive between electrons.
+///
+/// Every particle acts with the charge of [`Weight`] real particles of its kind. The
+/// velocity change is that of a single real particle, `q/m` times the pair force, so
+/// heavier macro-particles push harder but are not pushed harder themselves.
+///
+/// With [`RepulsionMethod::CellList`] only pairs closer than [`CELL_SIZE`] interact. Each
+/// such pair is evaluated once and its force is added to both electrons with opposite
+/// signs, so the pass conserves weighted momentum up to rounding. The neglected far field
+/// is at most `ELECTRON_REPULSION_FORCE / CELL_SIZE²` per electron beyond the cutoff and
+/// cancels for a uniform cloud, but a dense beam or sheath far away is ignored.
+/// [`RepulsionMethod::BarnesHut`] includes every electron and trades that truncation for
+/// the octree's `theta` approximation error; it is not pairwise symmetric.
 pub fn electron_repulsion(
     time: Res<Time>,
     chunks: Res<ElectronChunks>,
+    settings: Res<RepulsionSettings>,
     mut electrons: Query<&mut Velocity, With<Electron>>,
+    mut diagnostics: Diagnostics,
 ) {
-    let chunks = &chunks.0;
-    for chunk in chunks.iter() {
-        let neighbor_chunks = (-1..=1)
-            .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| IVec3::new(x, y, z))))
-            .filter(|v| *v != IVec3::new(0, 0, 0))
-            .map(|v| v + *chunk.0)
-            .filter_map(|v| chunks.get(&v))
-            .collect::<Vec<_>>();
-        for electron in chunk.1 {
-            let mut electron_velocity = match electrons.get_mut(electron.id) {
-                Ok(v) => v,
-                Err(e) => {
-                    warn!("Dead electron: {}", e);
-                    continue;
+    let task_pool = ComputeTaskPool::get_or_init(TaskPool::default);
+    let softening = settings.softening;
+    let sorted = chunks.electrons();
+
+    let forces: Vec<Vec3> = match settings.method {
+        RepulsionMethod::CellList => {
+            // every task accumulates into its own buffer, the buffers are summed afterwards
+            let buffers = chunks.cells.par_splat_map(task_pool, None, |cells| {
+                let mut forces = vec![Vec3::ZERO; sorted.len()];
+                let mut add_pair = |i: usize, j: usize| {
+                    let rel_pos = sorted[i].position - sorted[j].position;
+                    if rel_pos.length_squared() < CELL_SIZE * CELL_SIZE {
+                        let charge = sorted[i].charge * sorted[j].charge;
+                        let force = repulsion_force(rel_pos, charge, softening);
+                        forces[i] += force * sorted[j].weight;
+                        forces[j] -= force * sorted[i].weight;
+                    }
+                };
+                for (cell, range) in cells {
+                    let neighbours = half_shell()
+                        .filter_map(|offset| chunks.cell_range(*cell + offset))
+                        .collect::<Vec<_>>();
+                    for i in range.clone() {
+                        for j in i + 1..range.end {
+                            add_pair(i, j);
+                        }
+                        for j in neighbours.iter().flat_map(|r| r.clone()) {
+                            add_pair(i, j);
+                        }
+                    }
                 }
-            };
-            for other in neighbor_chunks
-                .iter()
-                .flat_map(|v| *v)
-                .chain(chunk.1.iter().filter(|e| e.id != electron.id))
-            {
-                let rel_pos = other.position - electron.position;
-                let force =
-                    -ELECTRON_REPULSION_FORCE / (rel_pos.length_squared()) * rel_pos.normalize();
-                electron_velocity.0 += force * time.delta_seconds();
-            }
+                forces
+            });
+            buffers
+                .into_iter()
+                .reduce(|mut total, buffer| {
+                    total.iter_mut().zip(buffer).for_each(|(t, f)| *t += f);
+                    total
+                })
+                .unwrap_or_default()
+        }
+        RepulsionMethod::BarnesHut => {
+            let tree = Octree::new(sorted.iter().map(|e| (e.position, e.charge * e.weight)));
+            sorted
+                .par_chunk_map(task_pool, PARALLEL_BATCH_SIZE, |batch| {
+                    batch
+                        .iter()
+                        .map(|electron| {
+                            electron.charge
+                                * tree.sum(electron.position, settings.theta, |rel_pos, charge| {
+                                    repulsion_force(rel_pos, charge, softening)
+                                })
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .into_iter()
+                .flatten()
+                .collect()
         }
+    };
+
+    diagnostics.add_measurement(&REPULSION_NET_FORCE, || {
+        sorted
+            .iter()
+            .zip(forces.iter())
+            .map(|(electron, force)| *force * electron.weight)
+            .sum::<Vec3>()
+            .length() as f64
+    });
+
+    for (electron, force) in sorted.iter().zip(forces) {
+        let mut electron_velocity = match electrons.get_mut(electron.id) {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("Dead electron: {}", e);
+                continue;
+            }
+        };
+        electron_velocity.0 += force / electron.mass * time.delta_seconds();
     }
 }
+
+pub fn electron_momentum_diagnostic(
+    electrons: Query<(&Velocity, &Weight, &Particle), With<Electron>>,
+    mut diagnostics: Diagnostics,
+) {
+    diagnostics.add_measurement(&ELECTRON_MOMENTUM, || {
+        electrons
+            .iter()
+            .map(|(v, w, particle)| v.0 * w.0 * particle.mass)
+            .sum::<Vec3>()
+            .length() as f64
+    });
+}
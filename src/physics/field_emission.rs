@@ -0,0 +1,76 @@
+use crate::constants::{EV_PER_ENERGY_UNIT, METRES_PER_UNIT};
+
+/// First Fowler–Nordheim constant in A eV V⁻².
+const FN_A: f32 = 1.541434e-6;
+/// Second Fowler–Nordheim constant in eV^(-3/2) V m⁻¹.
+const FN_B: f32 = 6.83089e9;
+/// Schottky constant e³ / 4πε₀ in eV² m V⁻¹, scales the barrier lowering by the field.
+const SCHOTTKY: f32 = 1.439965e-9;
+/// Charge of an electron in coulomb.
+const ELEMENTARY_CHARGE: f32 = 1.602177e-19;
+
+/// Field at a surface in V/m for a field given as electron acceleration in simulation units.
+pub fn field_in_volts_per_metre(acceleration: f32) -> f32 {
+    acceleration * EV_PER_ENERGY_UNIT / METRES_PER_UNIT
+}
+
+/// Murphy–Good form of the Fowler–Nordheim current density in A/m² for a surface field
+/// `field` in V/m and a work function `work_function` in eV.
+///
+/// `J = A F² / (φ t²) exp(-v B φ^(3/2) / F)` with Forbes' approximations of the barrier
+/// functions `v(f) = 1 - f + f ln(f) / 6` and `t(f) = 1 + f / 9 - f ln(f) / 18`, where
+/// `f = c F / φ²` is the scaled field. Past `f = 1` the barrier has vanished and the
+/// density is held at its value there.
+pub fn fowler_nordheim_current_density(field: f32, work_function: f32) -> f32 {
+    if field <= 0.0 || work_function <= 0.0 {
+        return 0.0;
+    }
+    let field = field.min(work_function * work_function / SCHOTTKY);
+    let f = SCHOTTKY * field / (work_function * work_function);
+    let v = 1.0 - f + f * f.ln() / 6.0;
+    let t = 1.0 + f / 9.0 - f * f.ln() / 18.0;
+    FN_A * field * field / (work_function * t * t)
+        * (-v * FN_B * work_function.powf(1.5) / field).exp()
+}
+
+/// Electrons per second emitted by `area` square metres at the given current density.
+pub fn electrons_per_second(current_density: f32, area: f32) -> f32 {
+    current_density * area / ELEMENTARY_CHARGE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_emission_without_a_pulling_field() {
+        assert_eq!(fowler_nordheim_current_density(0.0, 4.5), 0.0);
+        assert_eq!(fowler_nordheim_current_density(-5e9, 4.5), 0.0);
+    }
+
+    #[test]
+    fn murphy_good_reference_value() {
+        // tungsten-like surface at 5 V/nm: f = 0.356, v(f) = 0.583 and t(f) = 1.060
+        let density = fowler_nordheim_current_density(5e9, 4.5);
+        assert!((density / 3.794e9 - 1.0).abs() < 1e-3, "J = {density} A/m²");
+        // a weaker field is exponentially less
+        let weaker = fowler_nordheim_current_density(3e9, 4.5);
+        assert!((weaker / 3.514e5 - 1.0).abs() < 1e-3, "J = {weaker} A/m²");
+    }
+
+    #[test]
+    fn continuous_at_the_vanishing_barrier() {
+        let work_function = 4.5;
+        let barrier_free = work_function * work_function / SCHOTTKY;
+        let at = fowler_nordheim_current_density(barrier_free, work_function);
+        let below = fowler_nordheim_current_density(barrier_free * (1.0 - 1e-4), work_function);
+        assert!((below / at - 1.0).abs() < 1e-3, "{below} vs {at}");
+        assert_eq!(fowler_nordheim_current_density(2.0 * barrier_free, work_function), at);
+    }
+
+    #[test]
+    fn field_conversion() {
+        // an acceleration of 1 is 0.1 V across 1 mm
+        assert!((field_in_volts_per_metre(1.0) - 100.0).abs() < 1e-3);
+    }
+}
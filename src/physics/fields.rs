@@ -0,0 +1,422 @@
+use std::f32::consts::PI;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::constants::{EV_PER_ENERGY_UNIT, MU_0};
+use crate::structs::{
+    CurrentLoop, Cylinder, CylindricalCathode, Electron, FieldRegion, HelmholtzCoil,
+    MagneticDipole, MagneticField, Plate, PlateCathode, Solenoid,
+};
+
+/// How many current loops a solenoid is split into when summing its field.
+const SOLENOID_MAX_LOOPS: u32 = 24;
+
+/// Everything that contributes to the electric field, sampled per position.
+///
+/// The field is expressed as the acceleration it gives an electron and the potential is
+/// chosen so that electrons accelerate towards higher potential.
+#[derive(SystemParam)]
+pub struct ElectricSources<'w, 's> {
+    plate_cathodes: Query<
+        'w,
+        's,
+        (&'static Transform, &'static PlateCathode, &'static Plate),
+        Without<Electron>,
+    >,
+    cylindrical_cathodes: Query<
+        'w,
+        's,
+        (&'static Transform, &'static CylindricalCathode, &'static Cylinder),
+        Without<Electron>,
+    >,
+    regions: Query<'w, 's, (&'static Transform, &'static FieldRegion), Without<Electron>>,
+}
+
+impl<'w, 's> ElectricSources<'w, 's> {
+    pub fn field_at(&self, pos: Vec3) -> Vec3 {
+        let plates = self
+            .plate_cathodes
+            .iter()
+            .map(|(transform, cathode, plate)| plate_cathode_field(transform, cathode, plate, pos));
+        let cylinders = self
+            .cylindrical_cathodes
+            .iter()
+            .map(|(transform, cathode, cylinder)| {
+                cylindrical_cathode_field(transform, cathode, cylinder, pos)
+            });
+        let regions = self
+            .regions
+            .iter()
+            .map(|(transform, region)| field_region_field(transform, region, pos));
+        plates.chain(cylinders).chain(regions).sum()
+    }
+
+    pub fn potential_at(&self, pos: Vec3) -> f32 {
+        let plates = self
+            .plate_cathodes
+            .iter()
+            .map(|(transform, cathode, plate)| {
+                plate_cathode_potential(transform, cathode, plate, pos)
+            });
+        let cylinders = self
+            .cylindrical_cathodes
+            .iter()
+            .map(|(transform, cathode, cylinder)| {
+                cylindrical_cathode_potential(transform, cathode, cylinder, pos)
+            });
+        let regions = self
+            .regions
+            .iter()
+            .map(|(transform, region)| field_region_potential(transform, region, pos));
+        plates.chain(cylinders).chain(regions).sum()
+    }
+}
+
+/// Uniform field pushing electrons away from the plate on both sides, within its footprint.
+pub fn plate_cathode_field(
+    transform: &Transform,
+    cathode: &PlateCathode,
+    plate: &Plate,
+    pos: Vec3,
+) -> Vec3 {
+    let rel_pos = transform.rotation.inverse() * (pos - transform.translation);
+    if rel_pos.x.abs() > plate.width / 2.0 || rel_pos.y.abs() > plate.height / 2.0 {
+        return Vec3::ZERO;
+    }
+
+    let force = Vec3::new(0.0, 0.0, cathode.e_field.copysign(rel_pos.z));
+    transform.rotation * force
+}
+
+pub fn plate_cathode_potential(
+    transform: &Transform,
+    cathode: &PlateCathode,
+    plate: &Plate,
+    pos: Vec3,
+) -> f32 {
+    let rel_pos = transform.rotation.inverse() * (pos - transform.translation);
+    if rel_pos.x.abs() > plate.width / 2.0 || rel_pos.y.abs() > plate.height / 2.0 {
+        return 0.0;
+    }
+    cathode.e_field * rel_pos.z.abs()
+}
+
+/// Radial field around the cylinder axis (local y), `ro * (r + r2² / r)`.
+pub fn cylindrical_cathode_field(
+    transform: &Transform,
+    cathode: &CylindricalCathode,
+    cylinder: &Cylinder,
+    pos: Vec3,
+) -> Vec3 {
+    let rel_pos = transform.rotation.inverse() * (pos - transform.translation);
+    let radial = Vec3::new(rel_pos.x, 0.0, rel_pos.z);
+    let r = radial.length(); // electron position by radius
+    if r == 0.0 {
+        return Vec3::ZERO;
+    }
+
+    let r2 = cylinder.inner_radius;
+    let ro = cathode.e_field; // surface charge of the cylinder. Taking it as coefficient from ui.
+    let e_field = ro * (r + r2 * r2 / r); // resulting value of electric field
+
+    transform.rotation * (e_field * radial / r)
+}
+
+/// Integral of [`cylindrical_cathode_field`], zero on the cathode surface.
+pub fn cylindrical_cathode_potential(
+    transform: &Transform,
+    cathode: &CylindricalCathode,
+    cylinder: &Cylinder,
+    pos: Vec3,
+) -> f32 {
+    let rel_pos = transform.rotation.inverse() * (pos - transform.translation);
+    let r = Vec3::new(rel_pos.x, 0.0, rel_pos.z).length().max(f32::EPSILON);
+
+    let r2 = cylinder.inner_radius;
+    let primitive = |r: f32| {
+        let log_term = if r2 > 0.0 { r2 * r2 * r.ln() } else { 0.0 };
+        cathode.e_field * (r * r / 2.0 + log_term)
+    };
+    primitive(r) - primitive(cylinder.outer_radius.max(f32::EPSILON))
+}
+
+/// Uniform field inside the box of a [`FieldRegion`], zero outside.
+pub fn field_region_field(transform: &Transform, region: &FieldRegion, pos: Vec3) -> Vec3 {
+    let rel_pos = transform.rotation.inverse() * (pos - transform.translation);
+    if rel_pos.abs().cmpgt(region.half_extents).any() || region.half_extents.z <= 0.0 {
+        return Vec3::ZERO;
+    }
+    let acceleration = region.voltage / EV_PER_ENERGY_UNIT / (2.0 * region.half_extents.z);
+    transform.rotation * Vec3::new(0.0, 0.0, acceleration)
+}
+
+/// Potential of a [`FieldRegion`], zero on its -z face and held at the full voltage past
+/// the +z face, within the footprint of the box.
+pub fn field_region_potential(transform: &Transform, region: &FieldRegion, pos: Vec3) -> f32 {
+    let rel_pos = transform.rotation.inverse() * (pos - transform.translation);
+    let half = region.half_extents;
+    if rel_pos.x.abs() > half.x || rel_pos.y.abs() > half.y || half.z <= 0.0 {
+        return 0.0;
+    }
+    let fraction = (rel_pos.z.clamp(-half.z, half.z) + half.z) / (2.0 * half.z);
+    fraction * region.voltage / EV_PER_ENERGY_UNIT
+}
+
+/// Everything that contributes to the magnetic field, sampled per position.
+#[derive(SystemParam)]
+pub struct MagneticSources<'w, 's> {
+    uniform: Query<'w, 's, &'static MagneticField>,
+    loops: Query<'w, 's, (&'static Transform, &'static CurrentLoop), Without<Electron>>,
+    solenoids: Query<'w, 's, (&'static Transform, &'static Solenoid), Without<Electron>>,
+    helmholtz_coils: Query<'w, 's, (&'static Transform, &'static HelmholtzCoil), Without<Electron>>,
+    dipoles: Query<'w, 's, (&'static Transform, &'static MagneticDipole), Without<Electron>>,
+}
+
+impl<'w, 's> MagneticSources<'w, 's> {
+    pub fn field_at(&self, pos: Vec3) -> Vec3 {
+        let mut field = self.uniform.iter().map(|f| f.0).sum::<Vec3>();
+
+        for (transform, current_loop) in self.loops.iter() {
+            field += in_local_frame(transform, pos, |local| {
+                current_loop_field(current_loop.radius, current_loop.current, local)
+            });
+        }
+        for (transform, solenoid) in self.solenoids.iter() {
+            field += in_local_frame(transform, pos, |local| solenoid_field(solenoid, local));
+        }
+        for (transform, coil) in self.helmholtz_coils.iter() {
+            field += in_local_frame(transform, pos, |local| {
+                helmholtz_field(coil.radius, coil.current, local)
+            });
+        }
+        for (transform, dipole) in self.dipoles.iter() {
+            field += in_local_frame(transform, pos, |local| {
+                dipole_field(dipole.moment, dipole.core_radius, local)
+            });
+        }
+
+        field
+    }
+}
+
+/// Evaluates `f` in the source's own frame and rotates the result back to world space.
+fn in_local_frame(transform: &Transform, pos: Vec3, f: impl Fn(Vec3) -> Vec3) -> Vec3 {
+    transform.rotation * f(transform.rotation.inverse() * (pos - transform.translation))
+}
+
+/// Field of a circular loop of radius `radius` in the local xz plane, axis along local y.
+///
+/// Uses the exact off-axis expressions in terms of complete elliptic integrals.
+pub fn current_loop_field(radius: f32, current: f32, pos: Vec3) -> Vec3 {
+    let rho = (pos.x * pos.x + pos.z * pos.z).sqrt();
+    let y = pos.y;
+
+    let alpha2 = (radius - rho) * (radius - rho) + y * y;
+    let beta2 = (radius + rho) * (radius + rho) + y * y;
+    if alpha2 < 1e-6 {
+        // on the wire itself
+        return Vec3::ZERO;
+    }
+    let beta = beta2.sqrt();
+    let m = 1.0 - alpha2 / beta2;
+    let (k, e) = elliptic_k_e(m);
+    let c = MU_0 * current / (2.0 * PI);
+
+    let b_axial = c / beta * (k + (radius * radius - rho * rho - y * y) / alpha2 * e);
+    if rho < 1e-4 {
+        return Vec3::new(0.0, b_axial, 0.0);
+    }
+    let b_radial = c * y / (rho * beta) * (-k + (radius * radius + rho * rho + y * y) / alpha2 * e);
+
+    Vec3::new(b_radial * pos.x / rho, b_axial, b_radial * pos.z / rho)
+}
+
+/// Finite solenoid centred at the origin with its axis along local y, summed as evenly
+/// spaced loops.
+pub fn solenoid_field(solenoid: &Solenoid, pos: Vec3) -> Vec3 {
+    let loops = solenoid.turns.clamp(1, SOLENOID_MAX_LOOPS);
+    let current = solenoid.current * solenoid.turns as f32 / loops as f32;
+
+    (0..loops)
+        .map(|i| {
+            let y = if loops == 1 {
+                0.0
+            } else {
+                (i as f32 / (loops - 1) as f32 - 0.5) * solenoid.length
+            };
+            current_loop_field(solenoid.radius, current, pos - Vec3::new(0.0, y, 0.0))
+        })
+        .sum()
+}
+
+/// Two coaxial loops spaced by one radius, giving a nearly uniform field between them.
+pub fn helmholtz_field(radius: f32, current: f32, pos: Vec3) -> Vec3 {
+    let offset = Vec3::new(0.0, radius / 2.0, 0.0);
+    current_loop_field(radius, current, pos - offset)
+        + current_loop_field(radius, current, pos + offset)
+}
+
+/// Point dipole with moment along local y. Inside `core_radius` the field is held at the
+/// uniform value of a magnetised sphere so it stays finite.
+pub fn dipole_field(moment: f32, core_radius: f32, pos: Vec3) -> Vec3 {
+    let m = Vec3::new(0.0, moment, 0.0);
+    let r = pos.length();
+    if r < core_radius {
+        return MU_0 / (4.0 * PI) * 2.0 * m / (core_radius * core_radius * core_radius);
+    }
+    let r_hat = pos / r;
+    MU_0 / (4.0 * PI) * (3.0 * r_hat * m.dot(r_hat) - m) / (r * r * r)
+}
+
+/// Complete elliptic integrals of the first and second kind K(m), E(m) with parameter m = k²,
+/// via the arithmetic-geometric mean.
+fn elliptic_k_e(m: f32) -> (f32, f32) {
+    let mut a = 1.0_f32;
+    let mut g = (1.0 - m).max(0.0).sqrt();
+    let mut c = m.sqrt();
+    let mut sum = 0.5 * c * c;
+    let mut power = 0.5_f32;
+
+    for _ in 0..16 {
+        if c.abs() < 1e-7 {
+            break;
+        }
+        let next_a = (a + g) / 2.0;
+        c = (a - g) / 2.0;
+        g = (a * g).sqrt();
+        a = next_a;
+        power *= 2.0;
+        sum += power * c * c;
+    }
+
+    let k = PI / (2.0 * a);
+    (k, k * (1.0 - sum))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::Species;
+
+    fn assert_close(actual: Vec3, expected: Vec3, tolerance: f32) {
+        let error = (actual - expected).length() / expected.length();
+        assert!(error < tolerance, "{actual} vs {expected}, relative error {error}");
+    }
+
+    /// Biot–Savart sum over a loop split into straight pieces, the current circulating
+    /// right-handed about +y.
+    fn biot_savart_loop(radius: f32, current: f32, pos: Vec3) -> Vec3 {
+        const PIECES: usize = 4000;
+        let point = |i: usize| {
+            let phi = 2.0 * PI * i as f32 / PIECES as f32;
+            Vec3::new(radius * phi.cos(), 0.0, -radius * phi.sin())
+        };
+        (0..PIECES)
+            .map(|i| {
+                let (a, b) = (point(i), point(i + 1));
+                let r = pos - (a + b) / 2.0;
+                MU_0 * current / (4.0 * PI) * (b - a).cross(r) / r.length().powi(3)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn loop_field_on_the_axis() {
+        let (radius, current) = (12.0, 300.0);
+        for y in [0.0, 3.0, -7.5, 20.0, 60.0] {
+            let expected = MU_0 * current * radius * radius
+                / (2.0 * (radius * radius + y * y).powf(1.5));
+            let field = current_loop_field(radius, current, Vec3::new(0.0, y, 0.0));
+            assert_close(field, Vec3::new(0.0, expected, 0.0), 1e-4);
+        }
+    }
+
+    #[test]
+    fn loop_field_off_the_axis_follows_biot_savart() {
+        let (radius, current) = (12.0, 300.0);
+        for pos in [
+            Vec3::new(5.0, 3.0, 0.0),
+            Vec3::new(-4.0, -6.0, 7.0),
+            Vec3::new(20.0, 2.0, -3.0),
+            Vec3::new(0.5, 30.0, 0.5),
+        ] {
+            let expected = biot_savart_loop(radius, current, pos);
+            assert_close(current_loop_field(radius, current, pos), expected, 1e-3);
+        }
+    }
+
+    #[test]
+    fn helmholtz_field_is_uniform_at_the_centre() {
+        let (radius, current) = (20.0, 100.0);
+        // (4/5)^(3/2) μ₀ I / R for the pair
+        let expected = Vec3::new(0.0, 0.8_f32.powf(1.5) * MU_0 * current / radius, 0.0);
+        assert_close(helmholtz_field(radius, current, Vec3::ZERO), expected, 1e-4);
+
+        // the first non-vanishing correction goes as (r/R)⁴
+        for offset in [Vec3::X, Vec3::Y, Vec3::Z, Vec3::new(1.0, 1.0, -1.0).normalize()] {
+            let pos = offset * 0.1 * radius;
+            assert_close(helmholtz_field(radius, current, pos), expected, 1e-3);
+        }
+    }
+
+    #[test]
+    fn dipole_far_field() {
+        let moment = 5000.0;
+        let r = 40.0;
+        let scale = MU_0 / (4.0 * PI) * moment / (r * r * r);
+        // twice as strong along the moment as across it, and opposed to it there
+        let along = dipole_field(moment, 1.0, Vec3::new(0.0, r, 0.0));
+        assert_close(along, Vec3::new(0.0, 2.0 * scale, 0.0), 1e-5);
+        let across = dipole_field(moment, 1.0, Vec3::new(0.0, 0.0, r));
+        assert_close(across, Vec3::new(0.0, -scale, 0.0), 1e-5);
+
+        // far from a loop its field is that of a dipole of moment I π R²
+        let (radius, current) = (2.0, 50.0);
+        let moment = current * PI * radius * radius;
+        for pos in [Vec3::new(0.0, 80.0, 0.0), Vec3::new(60.0, 45.0, -20.0)] {
+            let expected = dipole_field(moment, 1.0, pos);
+            assert_close(current_loop_field(radius, current, pos), expected, 1e-2);
+        }
+    }
+
+    fn cathode() -> (CylindricalCathode, Cylinder) {
+        let cathode = CylindricalCathode {
+            e_field: 2.0,
+            emission_rate: 0.0,
+            emission_carry: 0.0,
+            species: Species::Electron,
+        };
+        let cylinder = Cylinder { inner_radius: 3.0, outer_radius: 4.0, height: 50.0 };
+        (cathode, cylinder)
+    }
+
+    #[test]
+    fn cylindrical_field_follows_the_cathode_transform() {
+        let (cathode, cylinder) = cathode();
+        let (r, r2) = (5.0, cylinder.inner_radius);
+        let magnitude = cathode.e_field * (r + r2 * r2 / r);
+
+        // moved off the origin, the field still points away from the cathode's own axis
+        let shifted = Transform::from_xyz(10.0, 0.0, -6.0);
+        let pos = shifted.translation + Vec3::new(3.0, 8.0, 4.0);
+        let field = cylindrical_cathode_field(&shifted, &cathode, &cylinder, pos);
+        assert!((field - magnitude * Vec3::new(0.6, 0.0, 0.8)).length() < 1e-4, "{field}");
+
+        // laid along world x, the field is radial in the yz plane whatever the x offset
+        let rotated = Transform::from_xyz(10.0, 2.0, -6.0)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+        for along in [-20.0, 0.0, 7.0] {
+            let pos = rotated.translation + Vec3::new(along, 0.0, r);
+            let field = cylindrical_cathode_field(&rotated, &cathode, &cylinder, pos);
+            assert!((field - magnitude * Vec3::Z).length() < 1e-4, "{field}");
+        }
+
+        let potential = |transform: &Transform, pos: Vec3| {
+            cylindrical_cathode_potential(transform, &cathode, &cylinder, pos)
+        };
+        let upright = potential(&Transform::IDENTITY, Vec3::new(0.0, 0.0, r));
+        let laid = potential(&rotated, rotated.translation + Vec3::new(7.0, -r, 0.0));
+        assert!((upright - laid).abs() < 1e-4, "{upright} vs {laid}");
+    }
+}
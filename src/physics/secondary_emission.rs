@@ -0,0 +1,52 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::physics::maxwellian_flux_energy;
+use crate::structs::SecondaryEmission;
+
+/// Smoothness factor of Vaughan's angular dependence, 1 for an ordinary surface and 2 for
+/// a polished one.
+const SMOOTHNESS: f32 = 1.0;
+/// Mean energy of emitted secondaries in eV.
+const SECONDARY_MEAN_ENERGY: f32 = 2.0;
+
+/// Vaughan's empirical yield for an impact of `energy` eV at angle θ from the normal.
+///
+/// `δ = δmax(θ) (w e^(1 - w))^k` with `w = (E - E0) / (Emax(θ) - E0)`, `k = 0.62` below
+/// the peak and `0.25` above it. Grazing impacts free electrons closer to the surface, so
+/// both the peak yield and the peak energy grow with θ:
+/// `δmax(θ) = δmax (1 + ks θ² / 2π)`, `Emax(θ) = Emax (1 + ks θ² / π)`.
+pub fn secondary_yield(emission: &SecondaryEmission, energy: f32, cos_theta: f32) -> f32 {
+    let theta = cos_theta.clamp(-1.0, 1.0).abs().acos();
+    let max_yield = emission.max_yield * (1.0 + SMOOTHNESS * theta * theta / (2.0 * PI));
+    let max_energy = emission.max_energy * (1.0 + SMOOTHNESS * theta * theta / PI);
+    if energy <= emission.threshold || max_energy <= emission.threshold {
+        return 0.0;
+    }
+
+    let w = (energy - emission.threshold) / (max_energy - emission.threshold);
+    let k = if w < 1.0 { 0.62 } else { 0.25 };
+    max_yield * (w * (1.0 - w).exp()).powf(k)
+}
+
+/// Whole number of secondaries for one impact, rounded up or down at random so the mean
+/// matches `mean_yield`.
+pub fn secondary_count(mean_yield: f32) -> u32 {
+    let whole = mean_yield.floor();
+    whole as u32 + (rand::random::<f32>() < mean_yield - whole) as u32
+}
+
+/// Direction from a cosine (Lambertian) distribution around `normal`.
+pub fn cosine_direction(normal: Vec3) -> Vec3 {
+    let (u, v) = normal.any_orthonormal_pair();
+    let sin_theta = rand::random::<f32>().sqrt();
+    let cos_theta = (1.0 - sin_theta * sin_theta).sqrt();
+    let phi = 2.0 * PI * rand::random::<f32>();
+    normal * cos_theta + (u * phi.cos() + v * phi.sin()) * sin_theta
+}
+
+/// Energy of a secondary in eV, a gamma distribution of shape 2 around the mean energy.
+pub fn secondary_energy() -> f32 {
+    maxwellian_flux_energy(SECONDARY_MEAN_ENERGY / 2.0)
+}
@@ -0,0 +1,164 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::structs::{
+    Cylinder, CylindricalCathode, Electrode, ElectrodeImpact, Plate,
+    PlateCathode, Thermal, ThermalSettings, ThermionicEmitter, Tip,
+};
+
+/// Stefan–Boltzmann constant in W m⁻² K⁻⁴.
+const STEFAN_BOLTZMANN: f32 = 5.670374e-8;
+/// Boltzmann constant in eV/K.
+const BOLTZMANN_EV: f32 = 8.617333e-5;
+/// Charge of an electron in coulomb, also joules per eV.
+const ELEMENTARY_CHARGE: f32 = 1.602177e-19;
+/// Heat capacity per square metre of radiating surface of a 0.5 mm nickel sheet, which
+/// radiates from both sides.
+const HEAT_CAPACITY_PER_AREA: f32 = 990.0;
+/// Time over which the impact power shown in the UI is averaged, in seconds.
+const POWER_AVERAGING: f32 = 0.5;
+/// Glow starts at the Draper point and is white hot here, in K.
+const GLOW_START: f32 = 798.0;
+const GLOW_WHITE: f32 = 3000.0;
+
+/// Power radiated to surroundings at `ambient` K by a grey body, in W.
+pub fn radiated_power(thermal: &Thermal, ambient: f32) -> f32 {
+    STEFAN_BOLTZMANN
+        * thermal.emissivity
+        * thermal.area
+        * (thermal.temperature.powi(4) - ambient.powi(4))
+}
+
+/// Richardson–Dushman current density `J = A T² exp(-φ / kT)` in A/m².
+pub fn richardson_current_density(temperature: f32, emitter: &ThermionicEmitter) -> f32 {
+    if temperature <= 0.0 {
+        return 0.0;
+    }
+    emitter.richardson_constant
+        * temperature
+        * temperature
+        * (-emitter.work_function / (BOLTZMANN_EV * temperature)).exp()
+}
+
+/// Whole surface of an electrode in m².
+fn surface_area(plate: Option<&Plate>, cylinder: Option<&Cylinder>, tip: Option<&Tip>) -> f32 {
+    let square_mm = if let Some(plate) = plate {
+        2.0 * (plate.width * plate.height
+            + plate.width * plate.depth
+            + plate.height * plate.depth)
+    } else if let Some(cylinder) = cylinder {
+        let (outer, inner) = (cylinder.outer_radius, cylinder.inner_radius);
+        let ends = outer * outer - inner * inner;
+        2.0 * PI * ((outer + inner) * cylinder.height + ends)
+    } else if let Some(tip) = tip {
+        let r = tip.radius;
+        2.0 * PI * r * (tip.length - 2.0 * r).max(0.0) + 4.0 * PI * r * r
+    } else {
+        0.0
+    };
+    // one simulation unit is a millimetre
+    square_mm * 1e-6
+}
+
+/// Colour of a body glowing at `temperature`, from dull red through orange and yellow to
+/// white, black below the Draper point.
+pub fn incandescence(temperature: f32) -> Color {
+    // coarse steps, so small fluctuations don't touch the material every frame
+    let temperature = (temperature / 10.0).round() * 10.0;
+    if temperature <= GLOW_START {
+        return Color::BLACK;
+    }
+    let t = ((temperature - GLOW_START) / (GLOW_WHITE - GLOW_START)).min(1.0);
+    let green = (1.6 * t - 0.2).clamp(0.0, 1.0);
+    let blue = (2.0 * t - 1.0).clamp(0.0, 1.0);
+    let brightness = (4.0 * t).min(1.0);
+    Color::rgb(brightness, green * brightness, blue * brightness)
+}
+
+/// Gives every electrode with a known shape a thermal model at the ambient temperature.
+#[allow(clippy::type_complexity)]
+pub fn attach_thermal_models(
+    mut commands: Commands,
+    settings: Res<ThermalSettings>,
+    electrodes: Query<
+        (Entity, Option<&Plate>, Option<&Cylinder>, Option<&Tip>),
+        Added<Electrode>,
+    >,
+) {
+    for (entity, plate, cylinder, tip) in electrodes.iter() {
+        let area = surface_area(plate, cylinder, tip);
+        if area <= 0.0 {
+            continue;
+        }
+        commands.entity(entity).try_insert(Thermal {
+            temperature: settings.ambient_temperature,
+            heat_capacity: HEAT_CAPACITY_PER_AREA * area,
+            emissivity: 0.3,
+            area,
+            impact_power: 0.0,
+        });
+    }
+}
+
+/// Heats electrodes by the kinetic energy of absorbed particles and their heaters, cools
+/// them by radiation, and sets the emission rate of heated cathodes from their temperature.
+///
+/// `C dT/dt = P_impacts + P_heater - σ ε A (T⁴ - T_ambient⁴)`, stepped explicitly since
+/// the thermal time constants are far longer than a tick.
+#[allow(clippy::type_complexity)]
+pub fn update_electrode_temperatures(
+    time: Res<Time>,
+    settings: Res<ThermalSettings>,
+    mut impacts: EventReader<ElectrodeImpact>,
+    mut electrodes: Query<(
+        Entity,
+        &mut Thermal,
+        Option<&mut ThermionicEmitter>,
+        Option<&mut PlateCathode>,
+        Option<&mut CylindricalCathode>,
+    )>,
+) {
+    let dt = time.delta_seconds();
+    if dt <= 0.0 {
+        return;
+    }
+
+    let mut deposited = HashMap::<Entity, f32>::new();
+    for impact in impacts.read() {
+        let joules = impact.energy * impact.weight * settings.charge_scale * ELEMENTARY_CHARGE;
+        *deposited.entry(impact.electrode).or_default() += joules;
+    }
+
+    let averaging = (dt / POWER_AVERAGING).min(1.0);
+    for (entity, mut thermal, emitter, plate_cathode, cylindrical_cathode) in
+        electrodes.iter_mut()
+    {
+        let impact_power = deposited.get(&entity).copied().unwrap_or(0.0) / dt;
+        thermal.impact_power += (impact_power - thermal.impact_power) * averaging;
+
+        let heater_power = emitter.as_ref().map_or(0.0, |emitter| emitter.heater_power);
+        let net_power =
+            impact_power + heater_power - radiated_power(&thermal, settings.ambient_temperature);
+        if thermal.heat_capacity > 0.0 {
+            thermal.temperature =
+                (thermal.temperature + net_power * dt / thermal.heat_capacity).max(0.0);
+        }
+
+        let Some(mut emitter) = emitter else {
+            continue;
+        };
+        emitter.current_density = richardson_current_density(thermal.temperature, &emitter);
+        // electrons per second over the emitting surface, in particles of weight 1
+        let particle_rate = |area: f32| {
+            emitter.current_density * area / ELEMENTARY_CHARGE / settings.charge_scale
+        };
+        if let Some(mut cathode) = plate_cathode {
+            let share = cathode.faces.sides().len() as f32 / 2.0;
+            cathode.emission_rate = particle_rate(thermal.area * share);
+        } else if let Some(mut cathode) = cylindrical_cathode {
+            cathode.emission_rate = particle_rate(thermal.area);
+        }
+    }
+}
@@ -1,7 +1,12 @@
 use bevy::prelude::*;
 
+use crate::constants::B_DEFAULT_VALUE;
 use crate::structs::{
-    Cylinder, CylindricalCathode, DestructionField, Electron, Plate, PlateCathode,
+    Collector, CurrentLoop, Cylinder, CylindricalCathode, DestructionField, DynodeGap,
+    Electrode, ElectrodeCurrent, Electron, EmittingFaces, FieldEmitter, FieldRegion,
+    FranckHertzSweep, Gas, GasSettings, HelmholtzCoil, MagneticDipole, Photocathode, Plate,
+    PlateCathode, SecondaryEmission, SelectedElectrode, Solenoid, Species, SweptRegion, Tip,
+    UiState,
 };
 
 #[derive(Component)]
@@ -10,11 +15,72 @@ struct CylindricalDiodeSceneEntity;
 #[derive(Component)]
 struct PlateDiodeSceneEntity;
 
+#[derive(Component)]
+struct MagneticFocusingSceneEntity;
+
+#[derive(Component)]
+struct MagneticMirrorSceneEntity;
+
+#[derive(Component)]
+struct HelmholtzCoilSceneEntity;
+
+#[derive(Component)]
+struct DipoleTrapSceneEntity;
+
+#[derive(Component)]
+struct FranckHertzSceneEntity;
+
+#[derive(Component)]
+struct PhotomultiplierSceneEntity;
+
+#[derive(Component)]
+struct FieldEmissionSceneEntity;
+
 #[derive(Default, Debug, Hash, PartialEq, Eq, Clone, Copy, States)]
 pub enum SelectedScene {
     #[default]
     CylindricalDiode,
     PlateDiode,
+    MagneticFocusing,
+    MagneticMirror,
+    HelmholtzCoil,
+    DipoleTrap,
+    FranckHertz,
+    Photomultiplier,
+    FieldEmission,
+}
+
+impl SelectedScene {
+    pub const ALL: [SelectedScene; 9] = [
+        SelectedScene::CylindricalDiode,
+        SelectedScene::PlateDiode,
+        SelectedScene::MagneticFocusing,
+        SelectedScene::MagneticMirror,
+        SelectedScene::HelmholtzCoil,
+        SelectedScene::DipoleTrap,
+        SelectedScene::FranckHertz,
+        SelectedScene::Photomultiplier,
+        SelectedScene::FieldEmission,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SelectedScene::CylindricalDiode => "Cylindrical diode",
+            SelectedScene::PlateDiode => "Plate diode",
+            SelectedScene::MagneticFocusing => "Magnetic focusing",
+            SelectedScene::MagneticMirror => "Magnetic mirror",
+            SelectedScene::HelmholtzCoil => "Helmholtz coil",
+            SelectedScene::DipoleTrap => "Dipole trap",
+            SelectedScene::FranckHertz => "Franck–Hertz tube",
+            SelectedScene::Photomultiplier => "Photomultiplier",
+            SelectedScene::FieldEmission => "Field emission array",
+        }
+    }
+
+    pub fn next(&self) -> SelectedScene {
+        let index = Self::ALL.iter().position(|s| s == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
 }
 
 pub fn scenes_plugin(app: &mut App) {
@@ -34,6 +100,62 @@ pub fn scenes_plugin(app: &mut App) {
         .add_systems(
             OnExit(SelectedScene::PlateDiode),
             despawn_scene::<PlateDiodeSceneEntity>,
+        )
+        .add_systems(
+            OnEnter(SelectedScene::MagneticFocusing),
+            (setup_magnetic_focusing, clear_uniform_field),
+        )
+        .add_systems(
+            OnExit(SelectedScene::MagneticFocusing),
+            (despawn_scene::<MagneticFocusingSceneEntity>, restore_uniform_field),
+        )
+        .add_systems(
+            OnEnter(SelectedScene::MagneticMirror),
+            (setup_magnetic_mirror, clear_uniform_field),
+        )
+        .add_systems(
+            OnExit(SelectedScene::MagneticMirror),
+            (despawn_scene::<MagneticMirrorSceneEntity>, restore_uniform_field),
+        )
+        .add_systems(
+            OnEnter(SelectedScene::HelmholtzCoil),
+            (setup_helmholtz_coil, clear_uniform_field),
+        )
+        .add_systems(
+            OnExit(SelectedScene::HelmholtzCoil),
+            (despawn_scene::<HelmholtzCoilSceneEntity>, restore_uniform_field),
+        )
+        .add_systems(
+            OnEnter(SelectedScene::DipoleTrap),
+            (setup_dipole_trap, clear_uniform_field),
+        )
+        .add_systems(
+            OnExit(SelectedScene::DipoleTrap),
+            (despawn_scene::<DipoleTrapSceneEntity>, restore_uniform_field),
+        )
+        .add_systems(
+            OnEnter(SelectedScene::FranckHertz),
+            setup_franck_hertz,
+        )
+        .add_systems(
+            OnExit(SelectedScene::FranckHertz),
+            (despawn_scene::<FranckHertzSceneEntity>, leave_franck_hertz),
+        )
+        .add_systems(
+            OnEnter(SelectedScene::Photomultiplier),
+            setup_photomultiplier,
+        )
+        .add_systems(
+            OnExit(SelectedScene::Photomultiplier),
+            despawn_scene::<PhotomultiplierSceneEntity>,
+        )
+        .add_systems(
+            OnEnter(SelectedScene::FieldEmission),
+            setup_field_emission,
+        )
+        .add_systems(
+            OnExit(SelectedScene::FieldEmission),
+            despawn_scene::<FieldEmissionSceneEntity>,
         );
 }
 
@@ -41,7 +163,9 @@ fn despawn_scene<T: Component>(
     mut commands: Commands,
     query: Query<Entity, With<T>>,
     electrons: Query<Entity, With<Electron>>,
+    mut selected: ResMut<SelectedElectrode>,
 ) {
+    selected.0 = None;
     for entity in query.iter() {
         commands.entity(entity).despawn_recursive();
     }
@@ -50,6 +174,16 @@ fn despawn_scene<T: Component>(
     }
 }
 
+/// Coil and magnet scenes start without the uniform field of the B slider, which would
+/// otherwise be added to theirs.
+fn clear_uniform_field(mut ui_state: ResMut<UiState>) {
+    ui_state.b_value = 0.0;
+}
+
+fn restore_uniform_field(mut ui_state: ResMut<UiState>) {
+    ui_state.b_value = B_DEFAULT_VALUE;
+}
+
 fn setup_plate_diode(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -58,14 +192,18 @@ fn setup_plate_diode(
     const HEIGHT: f32 = 200.0;
     const WIDTH: f32 = 80.0;
     const CATHODE_POS: Vec3 = Vec3::new(15.0, 0.0, 0.0);
-    let cathode_rot: Quat = Quat::from_rotation_y(0.5 * std::f32::consts::PI);
+    // front (+z) face towards the anode, where a photocathode emits
+    let cathode_rot: Quat = Quat::from_rotation_y(-0.5 * std::f32::consts::PI);
     const ANODE_POS: Vec3 = Vec3::new(-15.0, 0.0, 0.0);
     let anode_rot: Quat = Quat::from_rotation_y(0.5 * std::f32::consts::PI);
 
     // cathode plate
     let plate_cathode = PlateCathode {
         e_field: 10.0,
-        emmisivness: 80,
+        emission_rate: 800.0,
+        emission_carry: 0.0,
+        species: Species::Electron,
+        faces: EmittingFaces::Front,
     };
     let plate = Plate {
         height: HEIGHT,
@@ -89,6 +227,12 @@ fn setup_plate_diode(
             transform: plate_transform,
             ..Default::default()
         },
+        Electrode {
+            name: "Cathode".to_string(),
+            absorbing: true,
+            color: Color::rgb(0.0, 1.0, 0.0),
+        },
+        ElectrodeCurrent::default(),
         plate_cathode,
         plate,
         DestructionField { depth: 0.2 },
@@ -118,6 +262,12 @@ fn setup_plate_diode(
             transform: plate_transform,
             ..Default::default()
         },
+        Electrode {
+            name: "Anode".to_string(),
+            absorbing: true,
+            color: Color::rgb(1.0, 0.0, 0.0),
+        },
+        ElectrodeCurrent::default(),
         plate,
         DestructionField { depth: 0.8 },
         PlateDiodeSceneEntity,
@@ -191,7 +341,9 @@ fn setup_cylindrical_diode(
     }));
     let cylindrical_cathode = CylindricalCathode {
         e_field: 10.0,
-        emmisivness: 80,
+        emission_rate: 800.0,
+        emission_carry: 0.0,
+        species: Species::Electron,
     };
     let cylinder = Cylinder {
         inner_radius: 0.0,
@@ -208,6 +360,12 @@ fn setup_cylindrical_diode(
             },
             ..Default::default()
         },
+        Electrode {
+            name: "Cathode".to_string(),
+            absorbing: true,
+            color: Color::rgb(0.0, 1.0, 0.0),
+        },
+        ElectrodeCurrent::default(),
         cylinder,
         cylindrical_cathode,
         DestructionField { depth: 0.2 },
@@ -235,6 +393,12 @@ fn setup_cylindrical_diode(
             },
             ..default()
         },
+        Electrode {
+            name: "Anode".to_string(),
+            absorbing: true,
+            color: Color::rgb(1.0, 0.843, 0.0),
+        },
+        ElectrodeCurrent::default(),
         cylinder,
         DestructionField { depth: 0.8 },
         CylindricalDiodeSceneEntity,
@@ -254,3 +418,768 @@ fn setup_cylindrical_diode(
         CylindricalDiodeSceneEntity,
     );
 }
+
+fn spawn_coil_ring(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    material: Handle<StandardMaterial>,
+    radius: f32,
+    transform: Transform,
+    scene_component: impl Component,
+) {
+    let mesh = meshes.add(Mesh::from(Torus::new(radius - 0.5, radius + 0.5)));
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material,
+            transform,
+            ..default()
+        },
+        scene_component,
+    ));
+}
+
+fn setup_magnetic_focusing(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    const SIZE: f32 = 30.0;
+    const CATHODE_POS: Vec3 = Vec3::new(40.0, 0.0, 0.0);
+    const ANODE_POS: Vec3 = Vec3::new(-40.0, 0.0, 0.0);
+    const SOLENOID_RADIUS: f32 = 25.0;
+    const SOLENOID_LENGTH: f32 = 60.0;
+    let plate_rot = Quat::from_rotation_y(0.5 * std::f32::consts::PI);
+    // solenoid axis (local y) along the beam
+    let solenoid_rot = Quat::from_rotation_z(0.5 * std::f32::consts::PI);
+
+    // cathode plate
+    let plate = Plate {
+        height: SIZE,
+        width: SIZE,
+        depth: 1.0,
+    };
+    let mesh = meshes.add(Mesh::from(Cuboid::new(plate.width, plate.height, plate.depth)));
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material: materials.add(Color::rgb(0.0, 1.0, 0.0)),
+            transform: Transform::from_translation(CATHODE_POS).with_rotation(plate_rot),
+            ..Default::default()
+        },
+        Electrode {
+            name: "Cathode".to_string(),
+            absorbing: true,
+            color: Color::rgb(0.0, 1.0, 0.0),
+        },
+        ElectrodeCurrent::default(),
+        PlateCathode {
+            e_field: 5.0,
+            emission_rate: 200.0,
+            emission_carry: 0.0,
+            species: Species::Electron,
+            faces: EmittingFaces::Back,
+        },
+        plate,
+        DestructionField { depth: 0.2 },
+        MagneticFocusingSceneEntity,
+    ));
+
+    // anode plate
+    let plate = Plate {
+        height: SIZE,
+        width: SIZE,
+        depth: 1.0,
+    };
+    let mesh = meshes.add(Mesh::from(Cuboid::new(plate.width, plate.height, plate.depth)));
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material: materials.add(Color::rgb(1.0, 0.0, 0.0)),
+            transform: Transform::from_translation(ANODE_POS).with_rotation(plate_rot),
+            ..Default::default()
+        },
+        Electrode {
+            name: "Anode".to_string(),
+            absorbing: true,
+            color: Color::rgb(1.0, 0.0, 0.0),
+        },
+        ElectrodeCurrent::default(),
+        plate,
+        DestructionField { depth: 0.8 },
+        MagneticFocusingSceneEntity,
+    ));
+
+    // solenoid around the gap, drawn as a handful of windings
+    let solenoid = Solenoid {
+        radius: SOLENOID_RADIUS,
+        length: SOLENOID_LENGTH,
+        turns: 20,
+        current: 40.0,
+    };
+    commands.spawn((
+        TransformBundle::from_transform(Transform::from_rotation(solenoid_rot)),
+        solenoid,
+        MagneticFocusingSceneEntity,
+    ));
+    let coil_material = materials.add(Color::rgb(0.72, 0.45, 0.2));
+    for i in 0..7 {
+        let x = (i as f32 / 6.0 - 0.5) * SOLENOID_LENGTH;
+        spawn_coil_ring(
+            &mut commands,
+            &mut meshes,
+            coil_material.clone(),
+            SOLENOID_RADIUS,
+            Transform::from_translation(Vec3::new(x, 0.0, 0.0)).with_rotation(solenoid_rot),
+            MagneticFocusingSceneEntity,
+        );
+    }
+
+    // bounding box, destruction panels
+    for pos in [
+        Vec3::new(0.0, 0.0, SOLENOID_RADIUS),
+        Vec3::new(0.0, 0.0, -SOLENOID_RADIUS),
+    ] {
+        spawn_dp(&mut commands, pos, Quat::default(), MagneticFocusingSceneEntity);
+    }
+    for pos in [
+        Vec3::new(0.0, SOLENOID_RADIUS, 0.0),
+        Vec3::new(0.0, -SOLENOID_RADIUS, 0.0),
+    ] {
+        spawn_dp(
+            &mut commands,
+            pos,
+            Quat::from_rotation_x(0.5 * std::f32::consts::PI),
+            MagneticFocusingSceneEntity,
+        );
+    }
+    spawn_dp(
+        &mut commands,
+        CATHODE_POS + Vec3::new(1.0, 0.0, 0.0),
+        plate_rot,
+        MagneticFocusingSceneEntity,
+    );
+}
+
+fn setup_magnetic_mirror(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    const COIL_RADIUS: f32 = 12.0;
+    const COIL_POS: f32 = 35.0;
+    // coil axis (local y) along x
+    let coil_rot = Quat::from_rotation_z(0.5 * std::f32::consts::PI);
+
+    // small transparent emitter in the middle of the bottle
+    let plate = Plate {
+        height: 4.0,
+        width: 4.0,
+        depth: 1.0,
+    };
+    let mesh = meshes.add(Mesh::from(Cuboid::new(plate.width, plate.height, plate.depth)));
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material: materials.add(Color::rgb(0.0, 1.0, 0.0)),
+            ..Default::default()
+        },
+        Electrode {
+            name: "Emitter".to_string(),
+            absorbing: false,
+            color: Color::rgb(0.0, 1.0, 0.0),
+        },
+        ElectrodeCurrent::default(),
+        PlateCathode {
+            e_field: 0.0,
+            emission_rate: 50.0,
+            emission_carry: 0.0,
+            species: Species::Electron,
+            faces: EmittingFaces::Both,
+        },
+        plate,
+        DestructionField { depth: 0.2 },
+        MagneticMirrorSceneEntity,
+    ));
+
+    // mirror coils
+    let coil_material = materials.add(Color::rgb(0.72, 0.45, 0.2));
+    for x in [-COIL_POS, COIL_POS] {
+        let transform = Transform::from_translation(Vec3::new(x, 0.0, 0.0)).with_rotation(coil_rot);
+        commands.spawn((
+            TransformBundle::from_transform(transform),
+            CurrentLoop {
+                radius: COIL_RADIUS,
+                current: 300.0,
+            },
+            MagneticMirrorSceneEntity,
+        ));
+        spawn_coil_ring(
+            &mut commands,
+            &mut meshes,
+            coil_material.clone(),
+            COIL_RADIUS,
+            transform,
+            MagneticMirrorSceneEntity,
+        );
+    }
+
+    // bounding box, destruction panels
+    for x in [-COIL_POS - 20.0, COIL_POS + 20.0] {
+        spawn_dp(
+            &mut commands,
+            Vec3::new(x, 0.0, 0.0),
+            Quat::from_rotation_y(0.5 * std::f32::consts::PI),
+            MagneticMirrorSceneEntity,
+        );
+    }
+    for pos in [Vec3::new(0.0, 0.0, 40.0), Vec3::new(0.0, 0.0, -40.0)] {
+        spawn_dp(&mut commands, pos, Quat::default(), MagneticMirrorSceneEntity);
+    }
+    for pos in [Vec3::new(0.0, 40.0, 0.0), Vec3::new(0.0, -40.0, 0.0)] {
+        spawn_dp(
+            &mut commands,
+            pos,
+            Quat::from_rotation_x(0.5 * std::f32::consts::PI),
+            MagneticMirrorSceneEntity,
+        );
+    }
+}
+
+/// Fine beam tube: the uniform field between a Helmholtz pair bends the beam of an electron
+/// gun into a circle of radius `v / B`. The gun leans slightly along the field, so the beam
+/// winds into a helix instead of running back into the gun.
+fn setup_helmholtz_coil(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    const COIL_RADIUS: f32 = 30.0;
+    const GUN_VOLTAGE: f32 = 50.0;
+    const GAP: f32 = 4.0;
+    // coil axis (local y) along z, towards the camera, so the orbits lie in the xy plane
+    let coil_rot = Quat::from_rotation_x(0.5 * std::f32::consts::PI);
+    // gun normal (local z) along +x, ten degrees towards +z
+    let gun_rot = Quat::from_rotation_y(80.0_f32.to_radians());
+
+    let coil_transform = Transform::from_rotation(coil_rot);
+    commands.spawn((
+        TransformBundle::from_transform(coil_transform),
+        HelmholtzCoil {
+            radius: COIL_RADIUS,
+            current: 400.0,
+        },
+        HelmholtzCoilSceneEntity,
+    ));
+    let coil_material = materials.add(Color::rgb(0.72, 0.45, 0.2));
+    for z in [-COIL_RADIUS / 2.0, COIL_RADIUS / 2.0] {
+        spawn_coil_ring(
+            &mut commands,
+            &mut meshes,
+            coil_material.clone(),
+            COIL_RADIUS,
+            coil_transform.with_translation(Vec3::new(0.0, 0.0, z)),
+            HelmholtzCoilSceneEntity,
+        );
+    }
+
+    // electron gun: a small cathode emitting from its front face into an accelerating gap
+    let plate = Plate {
+        height: 2.0,
+        width: 2.0,
+        depth: 0.5,
+    };
+    let mesh = meshes.add(Mesh::from(Cuboid::new(plate.width, plate.height, plate.depth)));
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material: materials.add(Color::rgb(0.0, 1.0, 0.0)),
+            transform: Transform::from_rotation(gun_rot),
+            ..Default::default()
+        },
+        Electrode {
+            name: "Electron gun".to_string(),
+            absorbing: false,
+            color: Color::rgb(0.0, 1.0, 0.0),
+        },
+        ElectrodeCurrent::default(),
+        PlateCathode {
+            e_field: 0.0,
+            emission_rate: 50.0,
+            emission_carry: 0.0,
+            species: Species::Electron,
+            faces: EmittingFaces::Front,
+        },
+        plate,
+        DestructionField { depth: 0.2 },
+        HelmholtzCoilSceneEntity,
+    ));
+    commands.spawn((
+        TransformBundle::from_transform(
+            Transform::from_translation(gun_rot * Vec3::new(0.0, 0.0, GAP / 2.0 + 0.5))
+                .with_rotation(gun_rot),
+        ),
+        FieldRegion {
+            half_extents: Vec3::new(1.5, 1.5, GAP / 2.0),
+            voltage: GUN_VOLTAGE,
+        },
+        HelmholtzCoilSceneEntity,
+    ));
+
+    // bounding box, destruction panels
+    for x in [-2.0 * COIL_RADIUS, 2.0 * COIL_RADIUS] {
+        spawn_dp(
+            &mut commands,
+            Vec3::new(x, 0.0, 0.0),
+            Quat::from_rotation_y(0.5 * std::f32::consts::PI),
+            HelmholtzCoilSceneEntity,
+        );
+    }
+    for pos in [
+        Vec3::new(0.0, 2.0 * COIL_RADIUS, 0.0),
+        Vec3::new(0.0, -2.0 * COIL_RADIUS, 0.0),
+    ] {
+        spawn_dp(
+            &mut commands,
+            pos,
+            Quat::from_rotation_x(0.5 * std::f32::consts::PI),
+            HelmholtzCoilSceneEntity,
+        );
+    }
+    for z in [-COIL_RADIUS, COIL_RADIUS] {
+        spawn_dp(
+            &mut commands,
+            Vec3::new(0.0, 0.0, z),
+            Quat::default(),
+            HelmholtzCoilSceneEntity,
+        );
+    }
+}
+
+/// Electrons released beside a bar magnet gyrate along its dipole field lines, bounce
+/// between the converging fields over its poles and drift around it, as in a radiation belt.
+fn setup_dipole_trap(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    const MAGNET_RADIUS: f32 = 3.0;
+    const EMITTER_X: f32 = 25.0;
+    const SIZE: f32 = 60.0;
+
+    // bar magnet along y
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(bevy::prelude::Cylinder {
+                radius: MAGNET_RADIUS / 2.0,
+                half_height: MAGNET_RADIUS,
+            })),
+            material: materials.add(Color::rgb(0.8, 0.1, 0.1)),
+            ..Default::default()
+        },
+        MagneticDipole {
+            moment: 2.0e6,
+            core_radius: MAGNET_RADIUS,
+        },
+        DipoleTrapSceneEntity,
+    ));
+
+    // small transparent emitter on the magnetic equator
+    let plate = Plate {
+        height: 2.0,
+        width: 2.0,
+        depth: 0.5,
+    };
+    let mesh = meshes.add(Mesh::from(Cuboid::new(plate.width, plate.height, plate.depth)));
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material: materials.add(Color::rgb(0.0, 1.0, 0.0)),
+            transform: Transform::from_translation(Vec3::new(EMITTER_X, 0.0, 0.0)),
+            ..Default::default()
+        },
+        Electrode {
+            name: "Emitter".to_string(),
+            absorbing: false,
+            color: Color::rgb(0.0, 1.0, 0.0),
+        },
+        ElectrodeCurrent::default(),
+        PlateCathode {
+            e_field: 0.0,
+            emission_rate: 50.0,
+            emission_carry: 0.0,
+            species: Species::Electron,
+            faces: EmittingFaces::Both,
+        },
+        plate,
+        DestructionField { depth: 0.2 },
+        DipoleTrapSceneEntity,
+    ));
+
+    // bounding box, destruction panels
+    for x in [-SIZE, SIZE] {
+        spawn_dp(
+            &mut commands,
+            Vec3::new(x, 0.0, 0.0),
+            Quat::from_rotation_y(0.5 * std::f32::consts::PI),
+            DipoleTrapSceneEntity,
+        );
+    }
+    for pos in [Vec3::new(0.0, SIZE, 0.0), Vec3::new(0.0, -SIZE, 0.0)] {
+        spawn_dp(
+            &mut commands,
+            pos,
+            Quat::from_rotation_x(0.5 * std::f32::consts::PI),
+            DipoleTrapSceneEntity,
+        );
+    }
+    for z in [-SIZE, SIZE] {
+        spawn_dp(&mut commands, Vec3::new(0.0, 0.0, z), Quat::default(), DipoleTrapSceneEntity);
+    }
+}
+
+fn setup_franck_hertz(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut gas: ResMut<GasSettings>,
+    mut sweep: ResMut<FranckHertzSweep>,
+) {
+    const SIZE: f32 = 40.0;
+    const CATHODE_X: f32 = -14.0;
+    const GRID_X: f32 = 10.0;
+    const COLLECTOR_X: f32 = 14.0;
+    // small counter voltage between grid and collector, so electrons that lost their
+    // energy in an inelastic collision no longer reach the collector
+    const RETARDING_VOLTAGE: f32 = -1.5;
+    // plate normals (local z) along +x
+    let plate_rot = Quat::from_rotation_y(0.5 * std::f32::consts::PI);
+
+    *gas = GasSettings {
+        enabled: true,
+        gas: Gas::Mercury,
+        pressure: 3.0,
+    };
+    *sweep = FranckHertzSweep::default();
+
+    let mesh = meshes.add(Mesh::from(Cuboid::new(SIZE, SIZE, 0.5)));
+    let [cathode, _grid, collector] = [
+        ("Cathode", CATHODE_X, false, Color::rgb(0.0, 1.0, 0.0)),
+        ("Grid", GRID_X, false, Color::rgb(0.6, 0.6, 0.6)),
+        ("Collector", COLLECTOR_X, true, Color::rgb(1.0, 0.0, 0.0)),
+    ]
+    .map(|(name, x, absorbing, color)| {
+        commands
+            .spawn((
+                PbrBundle {
+                    mesh: mesh.clone(),
+                    material: materials.add(color),
+                    transform: Transform::from_xyz(x, 0.0, 0.0).with_rotation(plate_rot),
+                    ..Default::default()
+                },
+                Electrode {
+                    name: name.to_string(),
+                    absorbing,
+                    color,
+                },
+                ElectrodeCurrent::default(),
+                Plate {
+                    height: SIZE,
+                    width: SIZE,
+                    depth: 0.5,
+                },
+                DestructionField { depth: 0.5 },
+                FranckHertzSceneEntity,
+            ))
+            .id()
+    });
+    commands.entity(cathode).insert(PlateCathode {
+        e_field: 0.0,
+        emission_rate: 300.0,
+        emission_carry: 0.0,
+        species: Species::Electron,
+        faces: EmittingFaces::Front,
+    });
+    commands.entity(collector).insert(Collector);
+
+    // accelerating region between cathode and grid, retarding one between grid and collector
+    let [accelerating, _retarding] = [
+        (CATHODE_X, GRID_X, sweep.start),
+        (GRID_X, COLLECTOR_X, RETARDING_VOLTAGE),
+    ]
+    .map(|(from, to, voltage)| {
+        commands
+            .spawn((
+                TransformBundle::from_transform(
+                    Transform::from_xyz((from + to) / 2.0, 0.0, 0.0).with_rotation(plate_rot),
+                ),
+                FieldRegion {
+                    half_extents: Vec3::new(SIZE / 2.0, SIZE / 2.0, (to - from) / 2.0),
+                    voltage,
+                },
+                FranckHertzSceneEntity,
+            ))
+            .id()
+    });
+    commands.entity(accelerating).insert(SweptRegion);
+
+    // bounding box, destruction panels
+    spawn_dp(
+        &mut commands,
+        Vec3::new(CATHODE_X - 2.0, 0.0, 0.0),
+        plate_rot,
+        FranckHertzSceneEntity,
+    );
+    for pos in [Vec3::new(0.0, 0.0, SIZE / 2.0), Vec3::new(0.0, 0.0, -SIZE / 2.0)] {
+        spawn_dp(&mut commands, pos, Quat::default(), FranckHertzSceneEntity);
+    }
+    for pos in [Vec3::new(0.0, SIZE / 2.0, 0.0), Vec3::new(0.0, -SIZE / 2.0, 0.0)] {
+        spawn_dp(
+            &mut commands,
+            pos,
+            Quat::from_rotation_x(0.5 * std::f32::consts::PI),
+            FranckHertzSceneEntity,
+        );
+    }
+}
+
+/// The other scenes are evacuated tubes.
+fn leave_franck_hertz(mut gas: ResMut<GasSettings>, mut sweep: ResMut<FranckHertzSweep>) {
+    gas.enabled = false;
+    sweep.running = false;
+}
+
+fn setup_photomultiplier(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    const DYNODES: usize = 5;
+    /// distance between neighbouring stages along x
+    const STEP: f32 = 12.0;
+    /// stages alternate between y = HEIGHT and y = -HEIGHT
+    const HEIGHT: f32 = 6.0;
+    const STAGE_VOLTAGE: f32 = 100.0;
+    /// field free gap in front of every stage, so neighbouring gaps do not overlap
+    const MARGIN: f32 = 1.5;
+    const PLATE_WIDTH: f32 = 10.0;
+    const PLATE_HEIGHT: f32 = 20.0;
+
+    // photocathode, dynodes and anode zigzag along x
+    let stages = (0..DYNODES + 2)
+        .map(|i| {
+            let x = (i as f32 - (DYNODES + 1) as f32 / 2.0) * STEP;
+            let y = if i % 2 == 0 { HEIGHT } else { -HEIGHT };
+            Vec3::new(x, y, 0.0)
+        })
+        .collect::<Vec<_>>();
+    let directions = stages
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).normalize())
+        .collect::<Vec<_>>();
+
+    // every stage faces the electrons coming in and going out, local y along z
+    let mesh = meshes.add(Mesh::from(Cuboid::new(PLATE_WIDTH, PLATE_HEIGHT, 0.5)));
+    for (i, position) in stages.iter().enumerate() {
+        let normal = match i {
+            0 => directions[0],
+            i if i == DYNODES + 1 => -directions[DYNODES],
+            i => (directions[i] - directions[i - 1]).normalize(),
+        };
+        let (name, color) = match i {
+            0 => ("Photocathode".to_string(), Color::rgb(0.0, 1.0, 0.0)),
+            i if i == DYNODES + 1 => ("Anode".to_string(), Color::rgb(1.0, 0.0, 0.0)),
+            i => (format!("Dynode {i}"), Color::rgb(0.72, 0.45, 0.2)),
+        };
+        let mut stage = commands.spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: materials.add(color),
+                transform: Transform::from_translation(*position).looking_to(-normal, Vec3::Z),
+                ..Default::default()
+            },
+            Electrode {
+                name,
+                absorbing: true,
+                color,
+            },
+            ElectrodeCurrent::default(),
+            Plate {
+                height: PLATE_HEIGHT,
+                width: PLATE_WIDTH,
+                depth: 0.5,
+            },
+            DestructionField { depth: 0.5 },
+            PhotomultiplierSceneEntity,
+        ));
+        match i {
+            0 => stage.insert(Photocathode {
+                photon_rate: 40.0,
+                ..Default::default()
+            }),
+            i if i == DYNODES + 1 => stage.insert(Collector),
+            _ => stage.insert(SecondaryEmission {
+                max_yield: 3.0,
+                max_energy: 100.0,
+                threshold: 5.0,
+            }),
+        };
+    }
+
+    // accelerating gaps between consecutive stages
+    for (pair, direction) in stages.windows(2).zip(directions.iter()) {
+        let length = pair[0].distance(pair[1]);
+        commands.spawn((
+            TransformBundle::from_transform(
+                Transform::from_translation((pair[0] + pair[1]) / 2.0)
+                    .looking_to(-*direction, Vec3::Z),
+            ),
+            FieldRegion {
+                half_extents: Vec3::new(
+                    PLATE_WIDTH / 2.0,
+                    PLATE_HEIGHT / 2.0,
+                    length / 2.0 - MARGIN,
+                ),
+                voltage: STAGE_VOLTAGE,
+            },
+            DynodeGap,
+            PhotomultiplierSceneEntity,
+        ));
+    }
+
+    // bounding box, destruction panels
+    let half_length = (DYNODES + 1) as f32 / 2.0 * STEP + PLATE_WIDTH;
+    for x in [-half_length, half_length] {
+        spawn_dp(
+            &mut commands,
+            Vec3::new(x, 0.0, 0.0),
+            Quat::from_rotation_y(0.5 * std::f32::consts::PI),
+            PhotomultiplierSceneEntity,
+        );
+    }
+    for pos in [
+        Vec3::new(0.0, HEIGHT + PLATE_WIDTH, 0.0),
+        Vec3::new(0.0, -HEIGHT - PLATE_WIDTH, 0.0),
+    ] {
+        spawn_dp(
+            &mut commands,
+            pos,
+            Quat::from_rotation_x(0.5 * std::f32::consts::PI),
+            PhotomultiplierSceneEntity,
+        );
+    }
+    for z in [-PLATE_HEIGHT / 2.0 - 2.0, PLATE_HEIGHT / 2.0 + 2.0] {
+        spawn_dp(
+            &mut commands,
+            Vec3::new(0.0, 0.0, z),
+            Quat::default(),
+            PhotomultiplierSceneEntity,
+        );
+    }
+}
+
+fn setup_field_emission(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    const SIZE: f32 = 40.0;
+    const BASE_POS: Vec3 = Vec3::new(-12.0, 0.0, 0.0);
+    const ANODE_POS: Vec3 = Vec3::new(12.0, 0.0, 0.0);
+    const TIP_LENGTH: f32 = 6.0;
+    const TIP_RADIUS: f32 = 0.4;
+    const TIP_SPACING: f32 = 10.0;
+    /// extraction voltage between the emitter base and the anode
+    const VOLTAGE: f32 = 230.0;
+    let plate_rot = Quat::from_rotation_y(0.5 * std::f32::consts::PI);
+    // tip axis (local y) towards the anode
+    let tip_rot = Quat::from_rotation_z(-0.5 * std::f32::consts::PI);
+
+    // emitter base and anode plates
+    let plate_mesh = meshes.add(Mesh::from(Cuboid::new(SIZE, SIZE, 1.0)));
+    for (name, position, color) in [
+        ("Emitter base", BASE_POS, Color::rgb(0.0, 1.0, 0.0)),
+        ("Anode", ANODE_POS, Color::rgb(1.0, 0.0, 0.0)),
+    ] {
+        commands.spawn((
+            PbrBundle {
+                mesh: plate_mesh.clone(),
+                material: materials.add(color),
+                transform: Transform::from_translation(position).with_rotation(plate_rot),
+                ..Default::default()
+            },
+            Electrode {
+                name: name.to_string(),
+                absorbing: true,
+                color,
+            },
+            ElectrodeCurrent::default(),
+            Plate {
+                height: SIZE,
+                width: SIZE,
+                depth: 1.0,
+            },
+            DestructionField { depth: 0.5 },
+            FieldEmissionSceneEntity,
+        ));
+    }
+
+    // 3 × 3 array of tips standing on the base
+    let tip_mesh = meshes.add(Capsule3d::new(TIP_RADIUS, TIP_LENGTH - 2.0 * TIP_RADIUS));
+    let tip_color = Color::rgb(0.75, 0.75, 0.8);
+    for (i, (y, z)) in [-1.0, 0.0, 1.0]
+        .into_iter()
+        .flat_map(|y| [-1.0, 0.0, 1.0].map(|z| (y, z)))
+        .enumerate()
+    {
+        let position =
+            BASE_POS + Vec3::new(0.5 + TIP_LENGTH / 2.0, y * TIP_SPACING, z * TIP_SPACING);
+        commands.spawn((
+            PbrBundle {
+                mesh: tip_mesh.clone(),
+                material: materials.add(tip_color),
+                transform: Transform::from_translation(position).with_rotation(tip_rot),
+                ..Default::default()
+            },
+            Electrode {
+                name: format!("Tip {}", i + 1),
+                absorbing: true,
+                color: tip_color,
+            },
+            ElectrodeCurrent::default(),
+            Tip {
+                length: TIP_LENGTH,
+                radius: TIP_RADIUS,
+            },
+            FieldEmitter::default(),
+            DestructionField { depth: TIP_RADIUS },
+            FieldEmissionSceneEntity,
+        ));
+    }
+
+    // uniform extraction field between the plates
+    let gap = ANODE_POS.x - BASE_POS.x - 1.0;
+    commands.spawn((
+        TransformBundle::from_transform(
+            Transform::from_translation((BASE_POS + ANODE_POS) / 2.0)
+                .looking_to(Vec3::NEG_X, Vec3::Y),
+        ),
+        FieldRegion {
+            half_extents: Vec3::new(SIZE / 2.0, SIZE / 2.0, gap / 2.0),
+            voltage: VOLTAGE,
+        },
+        FieldEmissionSceneEntity,
+    ));
+
+    // bounding box, destruction panels
+    for (pos, rot) in [
+        (BASE_POS - Vec3::new(2.0, 0.0, 0.0), plate_rot),
+        (ANODE_POS + Vec3::new(2.0, 0.0, 0.0), plate_rot),
+        (Vec3::new(0.0, SIZE / 2.0 + 2.0, 0.0), Quat::from_rotation_x(0.5 * std::f32::consts::PI)),
+        (Vec3::new(0.0, -SIZE / 2.0 - 2.0, 0.0), Quat::from_rotation_x(0.5 * std::f32::consts::PI)),
+        (Vec3::new(0.0, 0.0, SIZE / 2.0 + 2.0), Quat::default()),
+        (Vec3::new(0.0, 0.0, -SIZE / 2.0 - 2.0), Quat::default()),
+    ] {
+        spawn_dp(&mut commands, pos, rot, FieldEmissionSceneEntity);
+    }
+}
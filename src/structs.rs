@@ -1,13 +1,251 @@
+use std::collections::VecDeque;
+
 use bevy::prelude::*;
+use bevy::utils::HashMap;
 
+/// Marks every simulated charged particle. Most particles are electrons, other kinds
+/// carry a [`Particle`] with a different charge and mass.
 #[derive(Component)]
 pub struct Electron;
 
+/// Charge in elementary charges and mass in electron masses.
+#[derive(Component, Clone, Copy, PartialEq)]
+pub struct Particle {
+    pub charge: f32,
+    pub mass: f32,
+}
+
+impl Particle {
+    /// Field sources give the acceleration of an electron, this turns it into the
+    /// acceleration of this particle, `-q/m`.
+    pub fn acceleration_scale(&self) -> f32 {
+        -self.charge / self.mass
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Species {
+    #[default]
+    Electron,
+    Positron,
+    HydrogenAnion,
+    Proton,
+    HeliumIon,
+    NeonIon,
+    ArgonIon,
+    MercuryIon,
+}
+
+impl Species {
+    pub const ALL: [Species; 8] = [
+        Species::Electron,
+        Species::Positron,
+        Species::HydrogenAnion,
+        Species::Proton,
+        Species::HeliumIon,
+        Species::NeonIon,
+        Species::ArgonIon,
+        Species::MercuryIon,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Species::Electron => "e⁻",
+            Species::Positron => "e⁺",
+            Species::HydrogenAnion => "H⁻",
+            Species::Proton => "H⁺",
+            Species::HeliumIon => "He⁺",
+            Species::NeonIon => "Ne⁺",
+            Species::ArgonIon => "Ar⁺",
+            Species::MercuryIon => "Hg⁺",
+        }
+    }
+
+    pub fn particle(&self) -> Particle {
+        let (charge, mass) = match self {
+            Species::Electron => (-1.0, 1.0),
+            Species::Positron => (1.0, 1.0),
+            Species::HydrogenAnion => (-1.0, 1837.2),
+            Species::Proton => (1.0, 1836.2),
+            Species::HeliumIon => (1.0, 7294.3),
+            Species::NeonIon => (1.0, 36785.0),
+            Species::ArgonIon => (1.0, 72820.0),
+            Species::MercuryIon => (1.0, 365600.0),
+        };
+        Particle { charge, mass }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Species::Electron => Color::rgb(0.0, 0.0, 1.0),
+            Species::Positron => Color::rgb(1.0, 0.0, 1.0),
+            Species::HydrogenAnion => Color::rgb(0.0, 0.8, 0.8),
+            Species::Proton => Color::rgb(1.0, 0.3, 0.0),
+            Species::HeliumIon => Color::rgb(1.0, 0.8, 0.0),
+            Species::NeonIon => Color::rgb(1.0, 0.4, 0.4),
+            Species::ArgonIon => Color::rgb(0.8, 0.0, 0.0),
+            Species::MercuryIon => Color::rgb(0.6, 0.6, 0.6),
+        }
+    }
+}
+
+/// Mesh and per-species materials shared by every particle.
+#[derive(Resource)]
+pub struct ParticleAssets {
+    mesh: Handle<Mesh>,
+    materials: Vec<(Species, Handle<StandardMaterial>)>,
+}
+
+impl FromWorld for ParticleAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Sphere::new(1.0).mesh().ico(3).unwrap());
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let materials = Species::ALL
+            .iter()
+            .map(|species| (*species, materials.add(species.color())))
+            .collect();
+        ParticleAssets { mesh, materials }
+    }
+}
+
+impl ParticleAssets {
+    /// Everything a freshly emitted particle is spawned with.
+    pub fn bundle(
+        &self,
+        species: Species,
+        position: Vec3,
+        velocity: Vec3,
+        weight: f32,
+        spawn_time: f32,
+    ) -> impl Bundle {
+        let material = self
+            .materials
+            .iter()
+            .find(|(s, _)| *s == species)
+            .map(|(_, material)| material.clone())
+            .unwrap_or_default();
+        (
+            PbrBundle {
+                mesh: self.mesh.clone(),
+                material,
+                transform: Transform::from_translation(position),
+                ..Default::default()
+            },
+            Electron,
+            species.particle(),
+            Velocity(velocity),
+            PreviousPosition(position),
+            SpawnTime(spawn_time),
+            Weight(weight),
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Gas {
+    Helium,
+    Neon,
+    Argon,
+    Mercury,
+}
+
+impl Gas {
+    pub const ALL: [Gas; 4] = [Gas::Helium, Gas::Neon, Gas::Argon, Gas::Mercury];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Gas::Helium => "Helium",
+            Gas::Neon => "Neon",
+            Gas::Argon => "Argon",
+            Gas::Mercury => "Mercury vapour",
+        }
+    }
+
+    /// Ion left behind by an ionizing collision.
+    pub fn ion(&self) -> Species {
+        match self {
+            Gas::Helium => Species::HeliumIon,
+            Gas::Neon => Species::NeonIon,
+            Gas::Argon => Species::ArgonIon,
+            Gas::Mercury => Species::MercuryIon,
+        }
+    }
+}
+
+/// Background gas filling the tube, vacuum while disabled.
+#[derive(Resource)]
+pub struct GasSettings {
+    pub enabled: bool,
+    pub gas: Gas,
+    /// in pascal, at room temperature
+    pub pressure: f32,
+}
+
+impl Default for GasSettings {
+    fn default() -> Self {
+        GasSettings {
+            enabled: false,
+            gas: Gas::Argon,
+            pressure: 1.0,
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct Velocity(pub Vec3);
 
+/// Position before the last step, so collisions can be tested along the whole step.
+#[derive(Component)]
+pub struct PreviousPosition(pub Vec3);
+
+/// Elapsed time when the electron was emitted.
+#[derive(Component)]
+pub struct SpawnTime(pub f32);
+
+/// Number of real electrons a simulated particle stands for.
+#[derive(Component)]
+pub struct Weight(pub f32);
+
+/// Weight given to newly emitted particles, so large currents need fewer entities.
+#[derive(Resource)]
+pub struct MacroParticles {
+    pub weight: f32,
+}
+
+impl Default for MacroParticles {
+    fn default() -> Self {
+        MacroParticles { weight: 1.0 }
+    }
+}
+
+/// Scale and surroundings of the electrode thermal model.
 #[derive(Resource)]
-pub struct SpawnTimer(pub Timer);
+pub struct ThermalSettings {
+    /// real electrons stood for by a particle of weight 1 when heating electrodes and
+    /// converting thermionic currents to emission rates
+    pub charge_scale: f32,
+    /// temperature of the surroundings electrodes radiate to, in K
+    pub ambient_temperature: f32,
+}
+
+impl Default for ThermalSettings {
+    fn default() -> Self {
+        ThermalSettings {
+            charge_scale: 1e16,
+            ambient_temperature: 300.0,
+        }
+    }
+}
+
+/// How cathodes turn their emission rate into whole particles each tick.
+#[derive(Resource, Default)]
+pub struct EmissionSettings {
+    /// draw every tick's count from a Poisson distribution instead of carrying the
+    /// fractional remainder over, adding shot noise to the current
+    pub poisson: bool,
+}
 
 #[derive(Component)]
 pub struct CameraAngles {
@@ -18,16 +256,85 @@ pub struct CameraAngles {
 #[derive(Component)]
 pub struct MagneticField(pub Vec3);
 
+/// Single circular current loop in the local xz plane, axis along local y.
+#[derive(Component)]
+pub struct CurrentLoop {
+    pub radius: f32,
+    pub current: f32,
+}
+
+/// Finite solenoid centred on its transform, axis along local y.
+#[derive(Component)]
+pub struct Solenoid {
+    pub radius: f32,
+    pub length: f32,
+    pub turns: u32,
+    pub current: f32,
+}
+
+/// Pair of coaxial loops one radius apart, axis along local y.
+#[derive(Component)]
+pub struct HelmholtzCoil {
+    pub radius: f32,
+    pub current: f32,
+}
+
+/// Permanent magnet modelled as a point dipole along local y.
+#[derive(Component)]
+pub struct MagneticDipole {
+    pub moment: f32,
+    pub core_radius: f32,
+}
+
 #[derive(Component)]
 pub struct PlateCathode {
     pub e_field: f32,
-    pub emmisivness: u32,
+    /// real particles per second, emitted as macro-particles of the current weight
+    pub emission_rate: f32,
+    /// fraction of a particle left over from the previous tick
+    pub emission_carry: f32,
+    pub species: Species,
+    pub faces: EmittingFaces,
+}
+
+/// Faces of a plate cathode that emit, the front one facing local +z.
+#[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EmittingFaces {
+    Front,
+    Back,
+    #[default]
+    Both,
+}
+
+impl EmittingFaces {
+    pub const ALL: [EmittingFaces; 3] = [EmittingFaces::Front, EmittingFaces::Back, EmittingFaces::Both];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmittingFaces::Front => "Front",
+            EmittingFaces::Back => "Back",
+            EmittingFaces::Both => "Both",
+        }
+    }
+
+    /// Signs of the local z axis the emitting faces point along.
+    pub fn sides(&self) -> &'static [f32] {
+        match self {
+            EmittingFaces::Front => &[1.0],
+            EmittingFaces::Back => &[-1.0],
+            EmittingFaces::Both => &[1.0, -1.0],
+        }
+    }
 }
 
 #[derive(Component)]
 pub struct CylindricalCathode {
     pub e_field: f32, //?
-    pub emmisivness: u32,
+    /// real particles per second, emitted as macro-particles of the current weight
+    pub emission_rate: f32,
+    /// fraction of a particle left over from the previous tick
+    pub emission_carry: f32,
+    pub species: Species,
 }
 
 #[derive(Component)]
@@ -44,17 +351,275 @@ pub struct Cylinder{
     pub height: f32,
 }
 
+/// Needle along local y with hemispherical ends, centred on its transform.
+#[derive(Component)]
+pub struct Tip {
+    /// end to end length
+    pub length: f32,
+    pub radius: f32,
+}
+
+impl Tip {
+    /// Centre of the hemisphere at the +y end, in the tip's own frame.
+    pub fn apex_center(&self) -> Vec3 {
+        Vec3::new(0.0, (self.length / 2.0 - self.radius).max(0.0), 0.0)
+    }
+}
+
+/// Fowler–Nordheim field emission from the apex of a [`Tip`].
+#[derive(Component)]
+pub struct FieldEmitter {
+    /// work function in eV
+    pub work_function: f32,
+    /// ratio of the field at the apex to the field applied around the tip
+    pub enhancement: f32,
+    /// effective emitting area in nm²
+    pub area: f32,
+    /// field at the apex in V/m, updated while emitting
+    pub surface_field: f32,
+    /// real electrons per second, updated while emitting
+    pub emission_rate: f32,
+    /// fraction of a particle left over from the previous tick
+    pub emission_carry: f32,
+}
+
+impl Default for FieldEmitter {
+    fn default() -> Self {
+        FieldEmitter {
+            work_function: 4.5,
+            enhancement: 200000.0,
+            area: 1.0,
+            surface_field: 0.0,
+            emission_rate: 0.0,
+            emission_carry: 0.0,
+        }
+    }
+}
+
+/// Lumped thermal model of an electrode: absorbed particles and a heater warm it up,
+/// thermal radiation cools it down.
+#[derive(Component)]
+pub struct Thermal {
+    /// temperature in K
+    pub temperature: f32,
+    /// heat capacity in J/K
+    pub heat_capacity: f32,
+    pub emissivity: f32,
+    /// radiating surface in m²
+    pub area: f32,
+    /// power brought in by absorbed particles in W, averaged over a fraction of a second
+    pub impact_power: f32,
+}
+
+/// Heated cathode whose emission rate follows its [`Thermal`] temperature by the
+/// Richardson–Dushman law.
+#[derive(Component)]
+pub struct ThermionicEmitter {
+    /// work function in eV
+    pub work_function: f32,
+    /// Richardson constant in A m⁻² K⁻², about half the theoretical 1.2e6 for real surfaces
+    pub richardson_constant: f32,
+    /// electrical heating in W
+    pub heater_power: f32,
+    /// real current density in A/m², updated while emitting
+    pub current_density: f32,
+}
+
+impl Default for ThermionicEmitter {
+    /// Bare tungsten with the heater off.
+    fn default() -> Self {
+        ThermionicEmitter {
+            work_function: 4.54,
+            richardson_constant: 600000.0,
+            heater_power: 0.0,
+            current_density: 0.0,
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct DestructionField {
     pub depth: f32,
 }
 
+/// Box with a uniform field along its local z axis, as between a cathode and a grid.
+#[derive(Component)]
+pub struct FieldRegion {
+    pub half_extents: Vec3,
+    /// potential rise in volts from the -z face to the +z face, positive voltages push
+    /// electrons towards +z
+    pub voltage: f32,
+}
+
+/// Secondary electron emission of an electrode surface, after Vaughan.
+#[derive(Component, Clone, Copy)]
+pub struct SecondaryEmission {
+    /// peak yield at normal incidence
+    pub max_yield: f32,
+    /// impact energy of the peak yield in eV
+    pub max_energy: f32,
+    /// impact energy in eV below which nothing is emitted
+    pub threshold: f32,
+}
+
+impl Default for SecondaryEmission {
+    fn default() -> Self {
+        SecondaryEmission {
+            max_yield: 2.0,
+            max_energy: 60.0,
+            threshold: 5.0,
+        }
+    }
+}
+
+/// Field region whose voltage is driven by the Franck–Hertz sweep.
+#[derive(Component)]
+pub struct SweptRegion;
+
+/// Output electrode of a tube, read by the Franck–Hertz sweep and the photomultiplier gain.
+#[derive(Component)]
+pub struct Collector;
+
+/// Field region between two stages of a dynode chain.
+#[derive(Component)]
+pub struct DynodeGap;
+
+/// Plate that emits an electron for a share of the photons falling on its front (+z) face.
+#[derive(Component)]
+pub struct Photocathode {
+    /// photons per second of the light source
+    pub photon_rate: f32,
+    /// wavelength of the light source in nm
+    pub wavelength: f32,
+    pub material: CathodeMaterial,
+    pub quantum_efficiency: f32,
+    /// photons of a single flash still to be delivered
+    pub pending_photons: u32,
+    /// emitted photoelectrons, counted in real electrons
+    pub photoelectrons: f32,
+}
+
+impl Photocathode {
+    /// Photon energy hν in eV.
+    pub fn photon_energy(&self) -> f32 {
+        crate::constants::PLANCK_EV_NM / self.wavelength
+    }
+
+    /// Kinetic energy hν − φ of the photoelectrons in eV, nothing is emitted when negative.
+    pub fn kinetic_energy(&self) -> f32 {
+        self.photon_energy() - self.material.work_function()
+    }
+}
+
+impl Default for Photocathode {
+    fn default() -> Self {
+        Photocathode {
+            photon_rate: 200.0,
+            wavelength: 400.0,
+            material: CathodeMaterial::Potassium,
+            quantum_efficiency: 0.25,
+            pending_photons: 0,
+            photoelectrons: 0.0,
+        }
+    }
+}
+
+/// Surface of a photocathode, setting its work function.
+#[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CathodeMaterial {
+    Cesium,
+    #[default]
+    Potassium,
+    Sodium,
+    Calcium,
+    Zinc,
+    Copper,
+    Platinum,
+}
+
+impl CathodeMaterial {
+    pub const ALL: [CathodeMaterial; 7] = [
+        CathodeMaterial::Cesium,
+        CathodeMaterial::Potassium,
+        CathodeMaterial::Sodium,
+        CathodeMaterial::Calcium,
+        CathodeMaterial::Zinc,
+        CathodeMaterial::Copper,
+        CathodeMaterial::Platinum,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CathodeMaterial::Cesium => "Cesium",
+            CathodeMaterial::Potassium => "Potassium",
+            CathodeMaterial::Sodium => "Sodium",
+            CathodeMaterial::Calcium => "Calcium",
+            CathodeMaterial::Zinc => "Zinc",
+            CathodeMaterial::Copper => "Copper",
+            CathodeMaterial::Platinum => "Platinum",
+        }
+    }
+
+    /// Work function φ in eV.
+    pub fn work_function(&self) -> f32 {
+        match self {
+            CathodeMaterial::Cesium => 2.14,
+            CathodeMaterial::Potassium => 2.29,
+            CathodeMaterial::Sodium => 2.36,
+            CathodeMaterial::Calcium => 2.87,
+            CathodeMaterial::Zinc => 4.33,
+            CathodeMaterial::Copper => 4.65,
+            CathodeMaterial::Platinum => 5.65,
+        }
+    }
+}
+
+/// A particle absorbed by an electrode.
+#[derive(Event, Clone, Copy)]
+pub struct ElectrodeImpact {
+    pub electrode: Entity,
+    /// where the particle's last step entered the electrode
+    pub position: Vec3,
+    /// surface normal pointing back to where the particle came from
+    pub normal: Vec3,
+    /// point just off the struck surface
+    pub surface_point: Vec3,
+    /// elapsed time of the impact, interpolated within the step
+    pub time: f32,
+    pub velocity: Vec3,
+    /// kinetic energy in eV
+    pub energy: f32,
+    pub particle: Particle,
+    pub weight: f32,
+}
+
+/// Anything in a scene the user can inspect and tweak from the settings window.
+#[derive(Component)]
+pub struct Electrode {
+    pub name: String,
+    pub absorbing: bool,
+    pub color: Color,
+}
+
+/// Charge collected by an absorbing electrode, in electron charges (an electron counts +1,
+/// a positive ion -1).
+#[derive(Component, Default)]
+pub struct ElectrodeCurrent {
+    /// collected since the current was last updated
+    pub collected: f32,
+    /// electrons per second, averaged over a short window
+    pub current: f32,
+    pub total: f32,
+}
+
+/// Electrode picked in the 3D view or in the settings window, highlighted on screen.
+#[derive(Resource, Default)]
+pub struct SelectedElectrode(pub Option<Entity>);
 
 #[derive(Resource)]
 pub struct UiState {
     pub phi_value: f32,
     pub theta_value: f32,
-    pub e_value: f32,
     pub b_value: f32,
     pub is_window_focused: bool
 }
@@ -62,3 +627,365 @@ pub struct UiState {
 #[derive(Component)]
 pub struct MagnetFieldArrow;
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SliceAxis {
+    X,
+    Y,
+    Z,
+}
+
+impl SliceAxis {
+    pub fn direction(&self) -> Vec3 {
+        match self {
+            SliceAxis::X => Vec3::X,
+            SliceAxis::Y => Vec3::Y,
+            SliceAxis::Z => Vec3::Z,
+        }
+    }
+
+    /// The two other axes, in right-handed order.
+    pub fn transverse(&self) -> (Vec3, Vec3) {
+        match self {
+            SliceAxis::X => (Vec3::Y, Vec3::Z),
+            SliceAxis::Y => (Vec3::Z, Vec3::X),
+            SliceAxis::Z => (Vec3::X, Vec3::Y),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum VectorFieldKind {
+    None,
+    Electric,
+    Magnetic,
+}
+
+/// What field overlays are drawn and where the cutting plane sits.
+#[derive(Resource)]
+pub struct FieldVisualization {
+    pub show_e_lines: bool,
+    pub show_b_lines: bool,
+    pub arrows: VectorFieldKind,
+    pub show_heatmap: bool,
+    pub slice_axis: SliceAxis,
+    pub slice_offset: f32,
+    /// half size of the slice plane
+    pub extent: f32,
+    /// field line seeds per side of the slice plane
+    pub seed_count: usize,
+    /// arrows per side of the slice plane
+    pub arrow_count: usize,
+    pub heatmap_resolution: usize,
+}
+
+impl Default for FieldVisualization {
+    fn default() -> Self {
+        FieldVisualization {
+            show_e_lines: false,
+            show_b_lines: false,
+            arrows: VectorFieldKind::None,
+            show_heatmap: false,
+            slice_axis: SliceAxis::Z,
+            slice_offset: 0.0,
+            extent: 50.0,
+            seed_count: 6,
+            arrow_count: 15,
+            heatmap_resolution: 64,
+        }
+    }
+}
+
+
+/// Where absorbed particles hit each electrode, accumulated into a texture per electrode.
+#[derive(Resource, Default)]
+pub struct ImpactHeatmapSettings {
+    /// paint the heatmaps onto the electrode meshes
+    pub enabled: bool,
+    /// set by the UI, every heatmap is written to a PNG file and the flag is cleared
+    pub export_requested: bool,
+    /// set by the UI, all accumulated impacts are discarded and the flag is cleared
+    pub clear_requested: bool,
+}
+
+/// Equipotential surfaces extracted from the electrode potential.
+#[derive(Resource)]
+pub struct IsosurfaceSettings {
+    pub enabled: bool,
+    pub levels: Vec<f32>,
+    /// grid points per axis
+    pub resolution: u32,
+    /// half size of the sampled cube around the origin
+    pub extent: f32,
+    pub opacity: f32,
+    /// potential range found in the last sampled grid, shown as a hint in the UI
+    pub sampled_range: (f32, f32),
+}
+
+impl Default for IsosurfaceSettings {
+    fn default() -> Self {
+        IsosurfaceSettings {
+            enabled: false,
+            levels: vec![50.0, 100.0],
+            resolution: 32,
+            extent: 50.0,
+            opacity: 0.35,
+            sampled_range: (0.0, 0.0),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RepulsionMethod {
+    /// pairs within one cell size, exact but truncated
+    CellList,
+    /// all pairs, far ones approximated with an octree
+    BarnesHut,
+}
+
+#[derive(Resource)]
+pub struct RepulsionSettings {
+    pub method: RepulsionMethod,
+    /// Barnes–Hut opening angle, smaller is more accurate and slower
+    pub theta: f32,
+    /// softening length of the Coulomb force
+    pub softening: f32,
+}
+
+impl Default for RepulsionSettings {
+    fn default() -> Self {
+        RepulsionSettings {
+            method: RepulsionMethod::CellList,
+            theta: 0.7,
+            softening: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CapPolicy {
+    /// cathodes emit only as many electrons as still fit under the cap, and any other
+    /// new particles over it are discarded
+    Throttle,
+    /// the oldest electrons are removed to make room
+    DropOldest,
+    /// close electrons are merged into heavier macro-particles, the oldest are removed
+    /// when there are not enough close pairs
+    Merge,
+}
+
+/// Upper bound on the number of simulated electrons.
+#[derive(Resource)]
+pub struct PopulationCap {
+    pub max_electrons: usize,
+    pub policy: CapPolicy,
+    /// set while the population is at the cap
+    pub active: bool,
+}
+
+impl Default for PopulationCap {
+    fn default() -> Self {
+        PopulationCap {
+            max_electrons: 20000,
+            policy: CapPolicy::Throttle,
+            active: false,
+        }
+    }
+}
+
+/// Which electrons the energy spectrum window shows.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum SpectrumSource {
+    /// at impact on the selected electrode
+    #[default]
+    SelectedElectrode,
+    /// currently in flight
+    InFlight,
+}
+
+/// Kinetic energy distribution of electrons, shown as a histogram.
+#[derive(Resource)]
+pub struct EnergySpectrum {
+    pub source: SpectrumSource,
+    /// in eV
+    pub bin_width: f32,
+    pub log_scale: bool,
+    /// latest `(energy in eV, weight)` of electrons absorbed by each electrode
+    pub impacts: HashMap<Entity, VecDeque<(f32, f32)>>,
+}
+
+impl Default for EnergySpectrum {
+    fn default() -> Self {
+        EnergySpectrum {
+            source: SpectrumSource::default(),
+            bin_width: 1.0,
+            log_scale: false,
+            impacts: HashMap::new(),
+        }
+    }
+}
+
+/// One rolling series of the plots window.
+pub struct Series {
+    pub name: String,
+    pub shown: bool,
+    /// `[elapsed seconds, value]` pairs, oldest first
+    pub points: VecDeque<[f64; 2]>,
+}
+
+/// Rolling history of simulation quantities, sampled a few times per second.
+#[derive(Resource)]
+pub struct TimeSeries {
+    pub paused: bool,
+    /// seconds of history kept
+    pub history: f32,
+    pub series: Vec<Series>,
+}
+
+impl Default for TimeSeries {
+    fn default() -> Self {
+        TimeSeries {
+            paused: false,
+            history: 60.0,
+            series: Vec::new(),
+        }
+    }
+}
+
+impl TimeSeries {
+    /// Appends a sample, starting a new series the first time `name` shows up.
+    pub fn push(&mut self, name: &str, time: f64, value: f64) {
+        let index = match self.series.iter().position(|series| series.name == name) {
+            Some(index) => index,
+            None => {
+                self.series.push(Series {
+                    name: name.to_string(),
+                    shown: self.series.is_empty(),
+                    points: VecDeque::new(),
+                });
+                self.series.len() - 1
+            }
+        };
+        self.series[index].points.push_back([time, value]);
+    }
+
+    /// Drops samples older than the history, and series left without any.
+    pub fn prune(&mut self, now: f64) {
+        let oldest = now - self.history as f64;
+        for series in &mut self.series {
+            while series.points.front().is_some_and(|[t, _]| *t < oldest) {
+                series.points.pop_front();
+            }
+        }
+        self.series.retain(|series| !series.points.is_empty());
+    }
+
+    /// All samples as `time,series,value` lines under a header.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("time,series,value\n");
+        for series in &self.series {
+            for [time, value] in &series.points {
+                csv += &format!("{time:.3},\"{}\",{value}\n", series.name.replace('"', "'"));
+            }
+        }
+        csv
+    }
+}
+
+/// What the phase space window plots about its chosen axis.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum PhaseSpaceView {
+    /// position along the axis against velocity along it
+    #[default]
+    Longitudinal,
+    /// distance from the axis against velocity away from it
+    Radial,
+    /// electrons per unit length along the axis
+    DensityProfile,
+    /// electrons per unit area around the axis, projected along it
+    RadialProfile,
+}
+
+impl PhaseSpaceView {
+    pub const ALL: [PhaseSpaceView; 4] = [
+        PhaseSpaceView::Longitudinal,
+        PhaseSpaceView::Radial,
+        PhaseSpaceView::DensityProfile,
+        PhaseSpaceView::RadialProfile,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PhaseSpaceView::Longitudinal => "Position – velocity",
+            PhaseSpaceView::Radial => "r – vr",
+            PhaseSpaceView::DensityProfile => "Density profile",
+            PhaseSpaceView::RadialProfile => "Radial profile",
+        }
+    }
+}
+
+/// Settings of the phase space window, the plots themselves are computed from the
+/// electrons while it is open.
+#[derive(Resource)]
+pub struct PhaseSpaceSettings {
+    pub view: PhaseSpaceView,
+    pub axis: SliceAxis,
+    /// bins of the density profiles
+    pub bins: usize,
+    /// only electrons moving along the beam with at least this kinetic energy along the
+    /// axis, in eV, count towards the emittance
+    pub min_axial_energy: f32,
+}
+
+impl Default for PhaseSpaceSettings {
+    fn default() -> Self {
+        PhaseSpaceSettings {
+            view: PhaseSpaceView::default(),
+            axis: SliceAxis::X,
+            bins: 50,
+            min_axial_energy: 1.0,
+        }
+    }
+}
+
+/// Automatic accelerating voltage sweep of the Franck–Hertz tube.
+#[derive(Resource)]
+pub struct FranckHertzSweep {
+    pub running: bool,
+    pub start: f32,
+    pub stop: f32,
+    pub step: f32,
+    /// time spent at every voltage, the current is averaged over its second half
+    pub dwell: f32,
+    pub voltage: f32,
+    pub elapsed: f32,
+    /// collector charge when the averaging of the current step began
+    pub collected_at: Option<f32>,
+    /// recorded `[voltage, collector current]` pairs
+    pub points: Vec<[f64; 2]>,
+}
+
+impl Default for FranckHertzSweep {
+    fn default() -> Self {
+        FranckHertzSweep {
+            running: false,
+            start: 0.0,
+            stop: 30.0,
+            step: 0.5,
+            dwell: 4.0,
+            voltage: 0.0,
+            elapsed: 0.0,
+            collected_at: None,
+            points: Vec::new(),
+        }
+    }
+}
+
+impl FranckHertzSweep {
+    pub fn restart(&mut self) {
+        self.running = true;
+        self.voltage = self.start;
+        self.elapsed = 0.0;
+        self.collected_at = None;
+        self.points.clear();
+    }
+}
@@ -1,5 +1,18 @@
-use crate::constants;
-use crate::structs::{CameraAngles, MagnetFieldArrow, MagneticField, UiState};
+use crate::constants::{self, EV_PER_ENERGY_UNIT};
+use crate::structs::{
+    CameraAngles, CapPolicy, CathodeMaterial, Collector, CurrentLoop, Cylinder, CylindricalCathode,
+    DynodeGap, Electrode, ElectrodeCurrent, Electron, EmissionSettings, EmittingFaces,
+    EnergySpectrum, FieldEmitter, FieldRegion, FieldVisualization, FranckHertzSweep, Gas,
+    GasSettings, HelmholtzCoil, ImpactHeatmapSettings, IsosurfaceSettings, MacroParticles,
+    MagnetFieldArrow, MagneticDipole, Particle, PhaseSpaceSettings, PhaseSpaceView, Photocathode,
+    Plate, PlateCathode, PopulationCap, RepulsionMethod, RepulsionSettings, SecondaryEmission,
+    SelectedElectrode, SliceAxis, Solenoid, Species, SpectrumSource, SweptRegion, Thermal,
+    ThermalSettings, ThermionicEmitter, TimeSeries, Tip, UiState, VectorFieldKind, Velocity, Weight,
+};
+use crate::physics::collisions::GAS_IONIZATIONS;
+use crate::physics::rms_emittance;
+use crate::physics::thermal::{incandescence, radiated_power};
+use bevy::diagnostic::DiagnosticsStore;
 use bevy::input::mouse::MouseMotion;
 use bevy::prelude::*;
 use bevy_egui::egui::{Id, Sense};
@@ -68,12 +81,50 @@ pub fn camera_controls(
     transform.rotation = angles.horizontal * angles.vertical;
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn ui_setup(
+    mut commands: Commands,
     mut ui_state: ResMut<UiState>,
     mut ctx: EguiContexts,
     mut clear_color: ResMut<ClearColor>,
     state: Res<State<SelectedScene>>,
     mut next_state: ResMut<NextState<SelectedScene>>,
+    mut selected: ResMut<SelectedElectrode>,
+    mut repulsion: ResMut<RepulsionSettings>,
+    mut cap: ResMut<PopulationCap>,
+    mut macro_particles: ResMut<MacroParticles>,
+    mut emission: ResMut<EmissionSettings>,
+    mut gas: ResMut<GasSettings>,
+    mut thermal_settings: ResMut<ThermalSettings>,
+    diagnostics: Res<DiagnosticsStore>,
+    mut electrodes: Query<(
+        Entity,
+        &mut Electrode,
+        Option<&ElectrodeCurrent>,
+        Option<&mut SecondaryEmission>,
+        Option<&mut PlateCathode>,
+        Option<&mut CylindricalCathode>,
+        Option<&mut Photocathode>,
+        Option<&mut FieldEmitter>,
+        Option<&mut Thermal>,
+        Option<&mut ThermionicEmitter>,
+        &Transform,
+        Option<&Plate>,
+    )>,
+    mut magnets: Query<
+        (
+            Option<&mut CurrentLoop>,
+            Option<&mut Solenoid>,
+            Option<&mut HelmholtzCoil>,
+            Option<&mut MagneticDipole>,
+        ),
+        Or<(
+            With<CurrentLoop>,
+            With<Solenoid>,
+            With<HelmholtzCoil>,
+            With<MagneticDipole>,
+        )>,
+    >,
 ) {
     ui_state.is_window_focused = false;
 
@@ -86,12 +137,8 @@ pub fn ui_setup(
         .show(ctx.ctx_mut(), |ui| {
             egui::ScrollArea::vertical().show(ui, |ui| {
                 // Здесь размещаете содержимое, которое может растягиваться вниз
-                let e_slider = ui.add(
-                    egui::Slider::new(&mut ui_state.e_value, 0.0001..=constants::E_MAX_VALUE)
-                        .text("E"),
-                );
                 let b_slider = ui.add(
-                    egui::Slider::new(&mut ui_state.b_value, 0.0001..=constants::B_MAX_VALUE)
+                    egui::Slider::new(&mut ui_state.b_value, 0.0..=constants::B_MAX_VALUE)
                         .text("B"),
                 );
 
@@ -112,7 +159,6 @@ pub fn ui_setup(
                 if ui
                     .interact(ui.max_rect(), Id::new("CUM"), Sense::click())
                     .clicked()
-                    || e_slider.dragged()
                     || b_slider.dragged()
                     || phi_slider.dragged()
                     || theta_slider.dragged()
@@ -129,10 +175,319 @@ pub fn ui_setup(
                     };
                 }
 
-                if ui.button("Change diode type").clicked() {
-                    match state.get() {
-                        SelectedScene::CylindricalDiode => next_state.set(SelectedScene::PlateDiode),
-                        SelectedScene::PlateDiode => next_state.set(SelectedScene::CylindricalDiode),
+                let mut scene = *state.get();
+                egui::ComboBox::from_label("Scene")
+                    .selected_text(scene.name())
+                    .show_ui(ui, |ui| {
+                        for option in SelectedScene::ALL {
+                            ui.selectable_value(&mut scene, option, option.name());
+                        }
+                    });
+                if scene != *state.get() {
+                    next_state.set(scene);
+                }
+
+                ui.separator();
+                egui::ComboBox::from_label("Repulsion")
+                    .selected_text(match repulsion.method {
+                        RepulsionMethod::CellList => "Cell list",
+                        RepulsionMethod::BarnesHut => "Barnes–Hut",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut repulsion.method,
+                            RepulsionMethod::CellList,
+                            "Cell list",
+                        );
+                        ui.selectable_value(
+                            &mut repulsion.method,
+                            RepulsionMethod::BarnesHut,
+                            "Barnes–Hut",
+                        );
+                    });
+                let softening_slider =
+                    ui.add(egui::Slider::new(&mut repulsion.softening, 0.0..=5.0).text("Softening"));
+                let theta_slider = (repulsion.method == RepulsionMethod::BarnesHut)
+                    .then(|| ui.add(egui::Slider::new(&mut repulsion.theta, 0.1..=1.5).text("θ")));
+                if softening_slider.dragged() || theta_slider.is_some_and(|s| s.dragged()) {
+                    ui_state.is_window_focused = true;
+                }
+
+                ui.separator();
+                let cap_slider = ui.add(
+                    egui::Slider::new(&mut cap.max_electrons, 100..=constants::MAX_ELECTRONS_VALUE)
+                        .logarithmic(true)
+                        .text("Max electrons"),
+                );
+                let weight_slider = ui.add(
+                    egui::Slider::new(&mut macro_particles.weight, 1.0..=constants::WEIGHT_MAX_VALUE)
+                        .logarithmic(true)
+                        .text("Particle weight"),
+                );
+                let scale_slider = ui.add(
+                    egui::Slider::new(&mut thermal_settings.charge_scale, 1e10..=1e18)
+                        .logarithmic(true)
+                        .text("Electrons per particle, thermal"),
+                );
+                if cap_slider.dragged() || weight_slider.dragged() || scale_slider.dragged() {
+                    ui_state.is_window_focused = true;
+                }
+                ui.checkbox(&mut emission.poisson, "Shot noise in emission");
+                let policy_name = |policy: CapPolicy| match policy {
+                    CapPolicy::Throttle => "Throttle emission",
+                    CapPolicy::DropOldest => "Drop oldest",
+                    CapPolicy::Merge => "Merge",
+                };
+                egui::ComboBox::from_label("At cap")
+                    .selected_text(policy_name(cap.policy))
+                    .show_ui(ui, |ui| {
+                        for policy in [CapPolicy::Throttle, CapPolicy::DropOldest, CapPolicy::Merge]
+                        {
+                            ui.selectable_value(&mut cap.policy, policy, policy_name(policy));
+                        }
+                    });
+                if cap.active {
+                    let action = match cap.policy {
+                        CapPolicy::Throttle => "emission throttled",
+                        CapPolicy::DropOldest => "dropping oldest electrons",
+                        CapPolicy::Merge => "merging electrons",
+                    };
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!("Population cap reached, {action}"),
+                    );
+                }
+
+                ui.separator();
+                ui.checkbox(&mut gas.enabled, "Background gas");
+                if gas.enabled {
+                    egui::ComboBox::from_label("Gas")
+                        .selected_text(gas.gas.name())
+                        .show_ui(ui, |ui| {
+                            for option in Gas::ALL {
+                                ui.selectable_value(&mut gas.gas, option, option.name());
+                            }
+                        });
+                    let pressure_slider = ui.add(
+                        egui::Slider::new(&mut gas.pressure, 0.01..=constants::PRESSURE_MAX_VALUE)
+                            .logarithmic(true)
+                            .text("Pressure, Pa"),
+                    );
+                    if pressure_slider.dragged() {
+                        ui_state.is_window_focused = true;
+                    }
+                    if let Some(ionizations) = diagnostics
+                        .get(&GAS_IONIZATIONS)
+                        .and_then(|d| d.smoothed())
+                    {
+                        ui.label(format!("Ionizations: {ionizations:.1} per tick"));
+                    }
+                }
+
+                ui.separator();
+                ui.label("Electrodes");
+                // plate outlines, to find the gap in front of photocathodes
+                let plates = electrodes
+                    .iter()
+                    .filter_map(|(entity, .., transform, plate)| {
+                        let plate = plate?;
+                        let half_size = Vec3::new(plate.width, plate.height, plate.depth) / 2.0;
+                        Some((entity, *transform, half_size))
+                    })
+                    .collect::<Vec<_>>();
+                for (
+                    entity,
+                    mut electrode,
+                    current,
+                    secondary_emission,
+                    plate_cathode,
+                    cylindrical_cathode,
+                    photocathode,
+                    field_emitter,
+                    thermal,
+                    thermionic_emitter,
+                    ..
+                ) in electrodes.iter_mut()
+                {
+                    let is_selected = selected.0 == Some(entity);
+                    if ui.selectable_label(is_selected, &electrode.name).clicked() {
+                        selected.0 = if is_selected { None } else { Some(entity) };
+                    }
+                    if !is_selected {
+                        continue;
+                    }
+
+                    ui.indent(entity, |ui| {
+                        let is_plate_cathode = plate_cathode.is_some();
+                        let is_cathode = is_plate_cathode || cylindrical_cathode.is_some();
+                        let heated = thermionic_emitter.is_some();
+                        let focused = match (plate_cathode, cylindrical_cathode) {
+                            (Some(mut cathode), _) => {
+                                let cathode = &mut *cathode;
+                                egui::ComboBox::from_label("Emitting faces")
+                                    .selected_text(cathode.faces.name())
+                                    .show_ui(ui, |ui| {
+                                        for option in EmittingFaces::ALL {
+                                            ui.selectable_value(
+                                                &mut cathode.faces,
+                                                option,
+                                                option.name(),
+                                            );
+                                        }
+                                    });
+                                cathode_controls(
+                                    ui,
+                                    &mut cathode.e_field,
+                                    &mut cathode.emission_rate,
+                                    &mut cathode.species,
+                                    heated,
+                                )
+                            }
+                            (None, Some(mut cathode)) => {
+                                let cathode = &mut *cathode;
+                                cathode_controls(
+                                    ui,
+                                    &mut cathode.e_field,
+                                    &mut cathode.emission_rate,
+                                    &mut cathode.species,
+                                    heated,
+                                )
+                            }
+                            (None, None) => {
+                                ui.label("Voltage: grounded");
+                                false
+                            }
+                        };
+                        if focused {
+                            ui_state.is_window_focused = true;
+                        }
+
+                        if is_plate_cathode {
+                            let mut photoemissive = photocathode.is_some();
+                            if ui.checkbox(&mut photoemissive, "Photoemission").changed() {
+                                if photoemissive {
+                                    commands.entity(entity).insert(Photocathode::default());
+                                } else {
+                                    commands.entity(entity).remove::<Photocathode>();
+                                }
+                            }
+                        }
+                        if let Some(mut photocathode) = photocathode {
+                            // the cathode voltage only stops electrons short of the next plate
+                            let gap = is_plate_cathode
+                                .then(|| gap_in_front(entity, &plates))
+                                .flatten();
+                            if light_source_controls(ui, &mut photocathode, gap) {
+                                ui_state.is_window_focused = true;
+                            }
+                        }
+                        if let Some(mut emitter) = field_emitter {
+                            if field_emitter_controls(ui, &mut emitter) {
+                                ui_state.is_window_focused = true;
+                            }
+                        }
+
+                        if let Some(mut thermal) = thermal {
+                            if is_cathode {
+                                let mut heated = heated;
+                                if ui.checkbox(&mut heated, "Heated (thermionic)").changed() {
+                                    if heated {
+                                        // heater sized to bring the cathode to its operating point
+                                        let operating = Thermal {
+                                            temperature: constants::HEATED_CATHODE_TEMPERATURE,
+                                            ..*thermal
+                                        };
+                                        commands.entity(entity).insert(ThermionicEmitter {
+                                            heater_power: radiated_power(
+                                                &operating,
+                                                thermal_settings.ambient_temperature,
+                                            ),
+                                            ..default()
+                                        });
+                                    } else {
+                                        commands.entity(entity).remove::<ThermionicEmitter>();
+                                    }
+                                }
+                            }
+                            if let Some(mut emitter) = thermionic_emitter {
+                                if thermionic_controls(ui, &mut emitter) {
+                                    ui_state.is_window_focused = true;
+                                }
+                            }
+                            if thermal_controls(ui, &mut thermal) {
+                                ui_state.is_window_focused = true;
+                            }
+                        }
+
+                        if let Some(current) = current {
+                            ui.label(format!("Current: {:.1} e/s", current.current));
+                            ui.label(format!("Collected: {:.0} e", current.total));
+                        }
+
+                        let mut absorbing = electrode.absorbing;
+                        if ui.checkbox(&mut absorbing, "Absorbing").changed() {
+                            electrode.absorbing = absorbing;
+                        }
+
+                        let mut emits = secondary_emission.is_some();
+                        if ui.checkbox(&mut emits, "Secondary emission").changed() {
+                            if emits {
+                                commands.entity(entity).insert(SecondaryEmission::default());
+                            } else {
+                                commands.entity(entity).remove::<SecondaryEmission>();
+                            }
+                        }
+                        if let Some(mut emission) = secondary_emission {
+                            let sliders = [
+                                ui.add(
+                                    egui::Slider::new(&mut emission.max_yield, 0.0..=10.0)
+                                        .text("Peak yield"),
+                                ),
+                                ui.add(
+                                    egui::Slider::new(&mut emission.max_energy, 10.0..=1000.0)
+                                        .logarithmic(true)
+                                        .text("Peak energy, eV"),
+                                ),
+                                ui.add(
+                                    egui::Slider::new(&mut emission.threshold, 0.0..=50.0)
+                                        .text("Threshold, eV"),
+                                ),
+                            ];
+                            if sliders.iter().any(|s| s.dragged()) {
+                                ui_state.is_window_focused = true;
+                            }
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.label("Colour");
+                            let [r, g, b, _] = electrode.color.as_rgba_f32();
+                            let mut rgb = [r, g, b];
+                            if egui::color_picker::color_edit_button_rgb(ui, &mut rgb).changed() {
+                                electrode.color = Color::rgb(rgb[0], rgb[1], rgb[2]);
+                            }
+                        });
+                    });
+                }
+
+                if !magnets.is_empty() {
+                    ui.separator();
+                    ui.label("Magnets");
+                    ui.label("Added to the uniform B above, which these scenes start at 0.");
+                }
+                for (current_loop, solenoid, helmholtz_coil, dipole) in magnets.iter_mut() {
+                    let (name, value, max) = if let Some(current_loop) = current_loop {
+                        ("Loop current", &mut current_loop.into_inner().current, constants::CURRENT_MAX_VALUE)
+                    } else if let Some(solenoid) = solenoid {
+                        ("Solenoid current", &mut solenoid.into_inner().current, constants::CURRENT_MAX_VALUE)
+                    } else if let Some(coil) = helmholtz_coil {
+                        ("Helmholtz current", &mut coil.into_inner().current, constants::CURRENT_MAX_VALUE)
+                    } else if let Some(dipole) = dipole {
+                        ("Dipole moment", &mut dipole.into_inner().moment, constants::DIPOLE_MAX_VALUE)
+                    } else {
+                        continue;
+                    };
+                    if ui.add(egui::Slider::new(value, -max..=max).text(name)).dragged() {
+                        ui_state.is_window_focused = true;
                     }
                 }
             });
@@ -140,11 +495,895 @@ pub fn ui_setup(
         .unwrap()
         .response;
 
-    if window_response.dragged() {
+    if window_response.dragged() || ctx.ctx_mut().is_pointer_over_area() {
         ui_state.is_window_focused = true;
     }
 }
 
+/// Toggles for the field line, arrow, potential and equipotential overlays.
+pub fn fields_window(
+    mut ui_state: ResMut<UiState>,
+    mut ctx: EguiContexts,
+    mut settings: ResMut<FieldVisualization>,
+    mut isosurfaces: ResMut<IsosurfaceSettings>,
+    mut impacts: ResMut<ImpactHeatmapSettings>,
+) {
+    let window_response = egui::Window::new("Fields")
+        .default_width(constants::SETTINGS_WINDOW_WIDTH)
+        .default_open(false)
+        .resizable(false)
+        .show(ctx.ctx_mut(), |ui| {
+            // bypass_change_detection so that only real edits trigger a recompute
+            let settings = settings.bypass_change_detection();
+            let mut changed = false;
+
+            changed |= ui.checkbox(&mut settings.show_e_lines, "E field lines").changed();
+            changed |= ui.checkbox(&mut settings.show_b_lines, "B field lines").changed();
+            changed |= ui.checkbox(&mut settings.show_heatmap, "Potential heatmap").changed();
+
+            ui.horizontal(|ui| {
+                ui.label("Arrows");
+                for (kind, name) in [
+                    (VectorFieldKind::None, "off"),
+                    (VectorFieldKind::Electric, "E"),
+                    (VectorFieldKind::Magnetic, "B"),
+                ] {
+                    changed |= ui.radio_value(&mut settings.arrows, kind, name).changed();
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Slice normal");
+                for (axis, name) in [(SliceAxis::X, "X"), (SliceAxis::Y, "Y"), (SliceAxis::Z, "Z")] {
+                    changed |= ui.radio_value(&mut settings.slice_axis, axis, name).changed();
+                }
+            });
+            let sliders = [
+                ui.add(
+                    egui::Slider::new(&mut settings.slice_offset, -100.0..=100.0).text("Offset"),
+                ),
+                ui.add(egui::Slider::new(&mut settings.extent, 5.0..=150.0).text("Extent")),
+                ui.add(egui::Slider::new(&mut settings.seed_count, 1..=16).text("Seeds")),
+                ui.add(egui::Slider::new(&mut settings.arrow_count, 2..=40).text("Arrows")),
+                ui.add(
+                    egui::Slider::new(&mut settings.heatmap_resolution, 2..=128).text("Resolution"),
+                ),
+            ];
+            for slider in sliders {
+                changed |= slider.changed();
+                if slider.dragged() {
+                    ui_state.is_window_focused = true;
+                }
+            }
+
+            ui.separator();
+            let isosurfaces = isosurfaces.bypass_change_detection();
+            let mut isosurfaces_changed =
+                ui.checkbox(&mut isosurfaces.enabled, "Equipotential surfaces").changed();
+            let (min, max) = isosurfaces.sampled_range;
+            ui.label(format!("Potential range: {:.1} .. {:.1}", min, max));
+            let mut removed = None;
+            for (i, level) in isosurfaces.levels.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    let drag = ui.add(egui::DragValue::new(level).speed(1.0).prefix("V = "));
+                    isosurfaces_changed |= drag.changed();
+                    if drag.dragged() {
+                        ui_state.is_window_focused = true;
+                    }
+                    if ui.button("−").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = removed {
+                isosurfaces.levels.remove(i);
+                isosurfaces_changed = true;
+            }
+            if ui.button("Add level").clicked() {
+                isosurfaces.levels.push((min + max) / 2.0);
+                isosurfaces_changed = true;
+            }
+            let sliders = [
+                ui.add(egui::Slider::new(&mut isosurfaces.resolution, 8..=64).text("Grid")),
+                ui.add(egui::Slider::new(&mut isosurfaces.extent, 5.0..=150.0).text("Extent")),
+                ui.add(egui::Slider::new(&mut isosurfaces.opacity, 0.05..=1.0).text("Opacity")),
+            ];
+            for slider in sliders {
+                isosurfaces_changed |= slider.changed();
+                if slider.dragged() {
+                    ui_state.is_window_focused = true;
+                }
+            }
+
+            ui.separator();
+            ui.checkbox(&mut impacts.enabled, "Impact heatmaps");
+            ui.horizontal(|ui| {
+                if ui.button("Export PNG").clicked() {
+                    impacts.export_requested = true;
+                }
+                if ui.button("Clear").clicked() {
+                    impacts.clear_requested = true;
+                }
+            });
+
+            (changed, isosurfaces_changed)
+        });
+
+    if let Some(response) = window_response {
+        let (changed, isosurfaces_changed) = response.inner.unwrap_or_default();
+        if changed {
+            settings.set_changed();
+        }
+        if isosurfaces_changed {
+            isosurfaces.set_changed();
+        }
+        if response.response.dragged() {
+            ui_state.is_window_focused = true;
+        }
+    }
+}
+
+/// Accelerating voltage sweep of the Franck–Hertz tube and its collector current plot.
+pub fn franck_hertz_window(
+    mut ui_state: ResMut<UiState>,
+    mut ctx: EguiContexts,
+    mut sweep: ResMut<FranckHertzSweep>,
+    mut regions: Query<&mut FieldRegion, With<SweptRegion>>,
+    collectors: Query<&ElectrodeCurrent, With<Collector>>,
+) {
+    let window_response = egui::Window::new("Franck–Hertz")
+        .default_width(2.0 * constants::SETTINGS_WINDOW_WIDTH)
+        .show(ctx.ctx_mut(), |ui| {
+            let mut dragged = false;
+            let current = collectors.iter().map(|c| c.current).sum::<f32>();
+            ui.label(format!("Collector current: {current:.1} e/s"));
+
+            if sweep.running {
+                ui.label(format!("Sweeping, U = {:.1} V", sweep.voltage));
+                if ui.button("Stop").clicked() {
+                    sweep.running = false;
+                }
+            } else {
+                for mut region in regions.iter_mut() {
+                    dragged |= ui
+                        .add(egui::Slider::new(&mut region.voltage, 0.0..=60.0).text("U, V"))
+                        .dragged();
+                }
+                let sweep = &mut *sweep;
+                ui.horizontal(|ui| {
+                    ui.label("Sweep");
+                    for (value, prefix, suffix) in [
+                        (&mut sweep.start, "from ", " V"),
+                        (&mut sweep.stop, "to ", " V"),
+                        (&mut sweep.step, "step ", " V"),
+                        (&mut sweep.dwell, "", " s"),
+                    ] {
+                        dragged |= ui
+                            .add(
+                                egui::DragValue::new(value)
+                                    .speed(0.1)
+                                    .clamp_range(0.0..=60.0)
+                                    .prefix(prefix)
+                                    .suffix(suffix),
+                            )
+                            .dragged();
+                    }
+                });
+                if ui.button("Start sweep").clicked() {
+                    sweep.restart();
+                }
+            }
+
+            let points = sweep.points.clone();
+            egui_plot::Plot::new("franck_hertz_plot")
+                .height(200.0)
+                .x_axis_label("U, V")
+                .y_axis_label("I, e/s")
+                .allow_scroll(false)
+                .show(ui, |plot| {
+                    plot.line(egui_plot::Line::new(egui_plot::PlotPoints::from(points.clone())));
+                    plot.points(egui_plot::Points::new(points).radius(2.0));
+                });
+            dragged
+        });
+
+    if let Some(response) = window_response {
+        if response.inner.unwrap_or_default() || response.response.dragged() {
+            ui_state.is_window_focused = true;
+        }
+    }
+}
+
+/// Rolling plots of the recorded time series, with series selection, pause and CSV export.
+pub fn plots_window(
+    mut ui_state: ResMut<UiState>,
+    mut ctx: EguiContexts,
+    mut time_series: ResMut<TimeSeries>,
+) {
+    let window_response = egui::Window::new("Plots")
+        .default_width(2.0 * constants::SETTINGS_WINDOW_WIDTH)
+        .default_open(false)
+        .show(ctx.ctx_mut(), |ui| {
+            let time_series = &mut *time_series;
+            ui.horizontal(|ui| {
+                let label = if time_series.paused { "Resume" } else { "Pause" };
+                if ui.button(label).clicked() {
+                    time_series.paused = !time_series.paused;
+                }
+                if ui.button("Clear").clicked() {
+                    time_series.series.clear();
+                }
+                if ui.button("Export CSV").clicked() {
+                    let path = "timeseries.csv";
+                    match std::fs::write(path, time_series.to_csv()) {
+                        Ok(()) => info!("Saved time series to {path}"),
+                        Err(e) => warn!("Could not save time series: {e}"),
+                    }
+                }
+            });
+            let dragged = ui
+                .add(
+                    egui::Slider::new(&mut time_series.history, 5.0..=600.0)
+                        .logarithmic(true)
+                        .text("History, s"),
+                )
+                .dragged();
+            ui.horizontal_wrapped(|ui| {
+                for series in &mut time_series.series {
+                    ui.checkbox(&mut series.shown, &series.name);
+                }
+            });
+
+            egui_plot::Plot::new("time_series_plot")
+                .height(200.0)
+                .x_axis_label("t, s")
+                .legend(egui_plot::Legend::default())
+                .allow_scroll(false)
+                .show(ui, |plot| {
+                    for series in time_series.series.iter().filter(|series| series.shown) {
+                        let points = series.points.iter().copied().collect::<Vec<_>>();
+                        plot.line(
+                            egui_plot::Line::new(egui_plot::PlotPoints::from(points))
+                                .name(&series.name),
+                        );
+                    }
+                });
+            dragged
+        });
+
+    if let Some(response) = window_response {
+        if response.inner.unwrap_or_default() || response.response.dragged() {
+            ui_state.is_window_focused = true;
+        }
+    }
+}
+
+/// Most points drawn in a phase space scatter plot, larger populations are subsampled.
+const PHASE_SPACE_MAX_POINTS: usize = 5000;
+
+/// Phase space scatter plots, transverse emittance and density profiles of the electrons
+/// about a chosen axis through the origin.
+pub fn phase_space_window(
+    mut ui_state: ResMut<UiState>,
+    mut ctx: EguiContexts,
+    mut settings: ResMut<PhaseSpaceSettings>,
+    electrons: Query<(&Transform, &Velocity, &Particle, &Weight), With<Electron>>,
+) {
+    let window_response = egui::Window::new("Phase space")
+        .default_width(2.0 * constants::SETTINGS_WINDOW_WIDTH)
+        .default_open(false)
+        .show(ctx.ctx_mut(), |ui| {
+            let settings = &mut *settings;
+            egui::ComboBox::from_label("Plot")
+                .selected_text(settings.view.name())
+                .show_ui(ui, |ui| {
+                    for option in PhaseSpaceView::ALL {
+                        ui.selectable_value(&mut settings.view, option, option.name());
+                    }
+                });
+            ui.horizontal(|ui| {
+                ui.label("Axis");
+                for (axis, name) in [(SliceAxis::X, "X"), (SliceAxis::Y, "Y"), (SliceAxis::Z, "Z")]
+                {
+                    ui.radio_value(&mut settings.axis, axis, name);
+                }
+            });
+            let sliders = [
+                ui.add(egui::Slider::new(&mut settings.bins, 5..=200).text("Bins")),
+                ui.add(
+                    egui::Slider::new(&mut settings.min_axial_energy, 0.01..=100.0)
+                        .logarithmic(true)
+                        .text("Min. axial energy for emittance, eV"),
+                ),
+            ];
+            let dragged = sliders.iter().any(|s| s.dragged());
+
+            let electron = Species::Electron.particle();
+            let axis = settings.axis.direction();
+            let (u_axis, v_axis) = settings.axis.transverse();
+            let samples = electrons
+                .iter()
+                .filter(|(_, _, particle, _)| **particle == electron)
+                .map(|(transform, velocity, _, weight)| {
+                    (transform.translation, velocity.0, weight.0)
+                })
+                .collect::<Vec<_>>();
+
+            // the beam runs the way most of the charge moves along the axis; slow and
+            // returning electrons would add huge slopes, so only the beam counts
+            let beam_direction = samples
+                .iter()
+                .map(|(_, v, w)| v.dot(axis) * w)
+                .sum::<f32>()
+                .signum()
+                * axis;
+            let min_speed = (2.0 * settings.min_axial_energy / EV_PER_ENERGY_UNIT).sqrt();
+            let beam = samples
+                .iter()
+                .filter(|(_, v, _)| v.dot(beam_direction) >= min_speed)
+                .collect::<Vec<_>>();
+            // trace space slopes u' = v_u / v_axis, in mrad
+            let slopes = |transverse: Vec3| {
+                beam.iter()
+                    .map(|(p, v, w)| {
+                        let slope = v.dot(transverse) / v.dot(beam_direction);
+                        (p.dot(transverse), 1000.0 * slope, *w)
+                    })
+                    .collect::<Vec<_>>()
+            };
+            ui.label(format!(
+                "{} electrons, {} in the beam, RMS emittance {:.3} / {:.3} mm·mrad",
+                samples.len(),
+                beam.len(),
+                rms_emittance(&slopes(u_axis)),
+                rms_emittance(&slopes(v_axis)),
+            ));
+
+            if samples.is_empty() {
+                return dragged;
+            }
+
+            let radial = |p: Vec3| p - axis * p.dot(axis);
+            let stride = samples.len() / PHASE_SPACE_MAX_POINTS + 1;
+            let plot = egui_plot::Plot::new("phase_space_plot")
+                .height(200.0)
+                .allow_scroll(false);
+            match settings.view {
+                PhaseSpaceView::Longitudinal | PhaseSpaceView::Radial => {
+                    let points = samples
+                        .iter()
+                        .step_by(stride)
+                        .map(|(p, v, _)| {
+                            if settings.view == PhaseSpaceView::Longitudinal {
+                                [p.dot(axis) as f64, v.dot(axis) as f64]
+                            } else {
+                                let r = radial(*p);
+                                let vr = v.dot(r.normalize_or_zero());
+                                [r.length() as f64, vr as f64]
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    let (x_label, y_label) = if settings.view == PhaseSpaceView::Longitudinal {
+                        ("position", "velocity")
+                    } else {
+                        ("r", "vr")
+                    };
+                    plot.x_axis_label(x_label)
+                        .y_axis_label(y_label)
+                        .show(ui, |plot| plot.points(egui_plot::Points::new(points).radius(1.5)));
+                }
+                PhaseSpaceView::DensityProfile | PhaseSpaceView::RadialProfile => {
+                    let along = settings.view == PhaseSpaceView::DensityProfile;
+                    let coordinate = |p: Vec3| if along { p.dot(axis) } else { radial(p).length() };
+                    let (min, max) = samples.iter().fold(
+                        (f32::INFINITY, f32::NEG_INFINITY),
+                        |(min, max), (p, _, _)| {
+                            let c = coordinate(*p);
+                            (min.min(c), max.max(c))
+                        },
+                    );
+                    let min = if along { min } else { 0.0 };
+                    let bins = settings.bins.max(1);
+                    let width = ((max - min) / bins as f32).max(f32::EPSILON);
+                    let mut counts = vec![0.0; bins];
+                    for (p, _, w) in &samples {
+                        let bin = ((coordinate(*p) - min) / width) as usize;
+                        counts[bin.min(bins - 1)] += w;
+                    }
+                    let points = counts
+                        .iter()
+                        .enumerate()
+                        .map(|(i, count)| {
+                            let low = min + i as f32 * width;
+                            let high = low + width;
+                            // per unit length along the axis, per unit area of the annulus
+                            let size = if along {
+                                width
+                            } else {
+                                std::f32::consts::PI * (high * high - low * low)
+                            };
+                            [((low + high) / 2.0) as f64, (count / size) as f64]
+                        })
+                        .collect::<Vec<_>>();
+                    let (x_label, y_label) = if along {
+                        ("position", "N per unit length")
+                    } else {
+                        ("r", "N per unit area")
+                    };
+                    plot.x_axis_label(x_label).y_axis_label(y_label).show(ui, |plot| {
+                        plot.line(egui_plot::Line::new(egui_plot::PlotPoints::from(points)))
+                    });
+                }
+            }
+            dragged
+        });
+
+    if let Some(response) = window_response {
+        if response.inner.unwrap_or_default() || response.response.dragged() {
+            ui_state.is_window_focused = true;
+        }
+    }
+}
+
+/// Upper bound on histogram bins, wider bins are used past it.
+const SPECTRUM_MAX_BINS: f32 = 500.0;
+
+/// Histogram of electron kinetic energies at impact on the selected electrode or in flight.
+pub fn energy_spectrum_window(
+    mut ui_state: ResMut<UiState>,
+    mut ctx: EguiContexts,
+    mut spectrum: ResMut<EnergySpectrum>,
+    selected: Res<SelectedElectrode>,
+    electrodes: Query<&Electrode>,
+    electrons: Query<(&Velocity, &Particle, &Weight), With<Electron>>,
+) {
+    let window_response = egui::Window::new("Energy spectrum")
+        .default_width(2.0 * constants::SETTINGS_WINDOW_WIDTH)
+        .default_open(false)
+        .show(ctx.ctx_mut(), |ui| {
+            let spectrum = &mut *spectrum;
+            ui.horizontal(|ui| {
+                ui.radio_value(
+                    &mut spectrum.source,
+                    SpectrumSource::SelectedElectrode,
+                    "Selected electrode",
+                );
+                ui.radio_value(&mut spectrum.source, SpectrumSource::InFlight, "In flight");
+            });
+            let dragged = ui
+                .add(
+                    egui::Slider::new(&mut spectrum.bin_width, 0.01..=100.0)
+                        .logarithmic(true)
+                        .text("Bin width, eV"),
+                )
+                .dragged();
+            ui.checkbox(&mut spectrum.log_scale, "Log scale");
+
+            let electron = Species::Electron.particle();
+            let samples = match spectrum.source {
+                SpectrumSource::SelectedElectrode => {
+                    let Some(entity) = selected.0 else {
+                        ui.label("Select an electrode to see the electrons it absorbs");
+                        return dragged;
+                    };
+                    if let Ok(electrode) = electrodes.get(entity) {
+                        ui.label(format!("Absorbed by {}", electrode.name));
+                    }
+                    if ui.button("Clear").clicked() {
+                        spectrum.impacts.remove(&entity);
+                    }
+                    spectrum
+                        .impacts
+                        .get(&entity)
+                        .map(|impacts| impacts.iter().copied().collect::<Vec<_>>())
+                        .unwrap_or_default()
+                }
+                SpectrumSource::InFlight => electrons
+                    .iter()
+                    .filter(|(_, particle, _)| **particle == electron)
+                    .map(|(velocity, _, weight)| {
+                        (0.5 * velocity.0.length_squared() * EV_PER_ENERGY_UNIT, weight.0)
+                    })
+                    .collect(),
+            };
+
+            let total = samples.iter().map(|(_, w)| w).sum::<f32>();
+            let max_energy = samples.iter().map(|(e, _)| *e).fold(0.0, f32::max);
+            if total > 0.0 {
+                let mean = samples.iter().map(|(e, w)| e * w).sum::<f32>() / total;
+                ui.label(format!(
+                    "{total:.0} electrons, mean {mean:.2} eV, max {max_energy:.2} eV"
+                ));
+            } else {
+                ui.label("No electrons yet");
+            }
+
+            let bin_width = spectrum.bin_width.max(max_energy / SPECTRUM_MAX_BINS);
+            if bin_width > spectrum.bin_width {
+                ui.label(format!("Bins widened to {bin_width:.2} eV"));
+            }
+            let bins = (max_energy / bin_width) as usize + 1;
+            let mut counts = vec![0.0; bins];
+            for (energy, weight) in &samples {
+                counts[((energy / bin_width) as usize).min(bins - 1)] += weight;
+            }
+            let bars = counts
+                .iter()
+                .enumerate()
+                .filter(|(_, count)| **count > 0.0)
+                .map(|(i, count)| {
+                    let height = if spectrum.log_scale {
+                        (1.0 + count).log10()
+                    } else {
+                        *count
+                    };
+                    egui_plot::Bar::new((i as f64 + 0.5) * bin_width as f64, height as f64)
+                        .width(bin_width as f64)
+                })
+                .collect();
+            egui_plot::Plot::new("energy_spectrum_plot")
+                .height(200.0)
+                .x_axis_label("E, eV")
+                .y_axis_label(if spectrum.log_scale {
+                    "log₁₀(1 + N)"
+                } else {
+                    "N"
+                })
+                .allow_scroll(false)
+                .show(ui, |plot| plot.bar_chart(egui_plot::BarChart::new(bars)));
+            dragged
+        });
+
+    if let Some(response) = window_response {
+        if response.inner.unwrap_or_default() || response.response.dragged() {
+            ui_state.is_window_focused = true;
+        }
+    }
+}
+
+/// Photons in a single flash of the photomultiplier window.
+const FLASH_PHOTONS: u32 = 100;
+
+pub fn photomultiplier_window(
+    mut ui_state: ResMut<UiState>,
+    mut ctx: EguiContexts,
+    mut photocathodes: Query<&mut Photocathode>,
+    mut gaps: Query<&mut FieldRegion, With<DynodeGap>>,
+    mut collectors: Query<&mut ElectrodeCurrent, With<Collector>>,
+) {
+    let window_response = egui::Window::new("Photomultiplier")
+        .default_width(constants::SETTINGS_WINDOW_WIDTH)
+        .show(ctx.ctx_mut(), |ui| {
+            let mut dragged = false;
+            for mut photocathode in photocathodes.iter_mut() {
+                dragged |= ui
+                    .add(
+                        egui::Slider::new(&mut photocathode.photon_rate, 0.0..=500.0)
+                            .text("Photons per second"),
+                    )
+                    .dragged();
+                dragged |= ui
+                    .add(
+                        egui::Slider::new(&mut photocathode.quantum_efficiency, 0.0..=1.0)
+                            .text("Quantum efficiency"),
+                    )
+                    .dragged();
+                if ui.button(format!("Flash ({FLASH_PHOTONS} photons)")).clicked() {
+                    photocathode.pending_photons += FLASH_PHOTONS;
+                }
+            }
+
+            // every stage sits at the same voltage above the previous one
+            let mut stage_voltage = gaps.iter().next().map_or(0.0, |gap| gap.voltage);
+            let response = ui
+                .add(egui::Slider::new(&mut stage_voltage, 0.0..=300.0).text("Stage voltage, V"));
+            if response.changed() {
+                for mut gap in gaps.iter_mut() {
+                    gap.voltage = stage_voltage;
+                }
+            }
+            dragged |= response.dragged();
+
+            let photoelectrons = photocathodes.iter().map(|p| p.photoelectrons).sum::<f32>();
+            let collected = collectors.iter().map(|c| c.total).sum::<f32>();
+            let current = collectors.iter().map(|c| c.current).sum::<f32>();
+            ui.label(format!("Photoelectrons: {photoelectrons:.0}"));
+            ui.label(format!("Anode charge: {collected:.0} e, current: {current:.1} e/s"));
+            if photoelectrons > 0.0 {
+                ui.label(format!("Gain: {:.1}", collected / photoelectrons));
+            } else {
+                ui.label("Gain: -");
+            }
+            if ui.button("Reset counters").clicked() {
+                for mut photocathode in photocathodes.iter_mut() {
+                    photocathode.photoelectrons = 0.0;
+                }
+                for mut collector in collectors.iter_mut() {
+                    collector.total = 0.0;
+                }
+            }
+            dragged
+        });
+
+    if let Some(response) = window_response {
+        if response.inner.unwrap_or_default() || response.response.dragged() {
+            ui_state.is_window_focused = true;
+        }
+    }
+}
+
+/// Emitted species, voltage and emission rate of a cathode, returns whether a slider is dragged.
+/// The emission rate of a heated cathode follows its temperature and is only shown.
+fn cathode_controls(
+    ui: &mut egui::Ui,
+    e_field: &mut f32,
+    emission_rate: &mut f32,
+    species: &mut Species,
+    heated: bool,
+) -> bool {
+    egui::ComboBox::from_label("Emits")
+        .selected_text(species.name())
+        .show_ui(ui, |ui| {
+            for option in Species::ALL {
+                ui.selectable_value(species, option, option.name());
+            }
+        });
+    // negative voltages retard the emitted electrons, as in the stopping potential experiment
+    let voltage_slider = ui.add(
+        egui::Slider::new(e_field, -constants::E_MAX_VALUE..=constants::E_MAX_VALUE)
+            .text("Voltage"),
+    );
+    if heated {
+        ui.label(format!("Emission: {emission_rate:.1} 1/s"));
+        return voltage_slider.dragged();
+    }
+    let emission_slider = ui.add(
+        egui::Slider::new(emission_rate, 0.0..=constants::EMISSION_RATE_MAX_VALUE)
+            .text("Emission, 1/s"),
+    );
+    voltage_slider.dragged() || emission_slider.dragged()
+}
+
+/// Heater and surface of a thermionic cathode, returns whether a slider is dragged.
+fn thermionic_controls(ui: &mut egui::Ui, emitter: &mut ThermionicEmitter) -> bool {
+    let sliders = [
+        ui.add(
+            egui::Slider::new(&mut emitter.heater_power, 0.0..=10000.0)
+                .logarithmic(true)
+                .text("Heater, W"),
+        ),
+        ui.add(
+            egui::Slider::new(&mut emitter.work_function, 1.0..=6.0).text("Work function, eV"),
+        ),
+    ];
+    ui.label(format!("Current density: {:.3e} A/m²", emitter.current_density));
+    sliders.iter().any(|s| s.dragged())
+}
+
+/// Temperature readout and thermal properties of an electrode, returns whether a slider is
+/// dragged.
+fn thermal_controls(ui: &mut egui::Ui, thermal: &mut Thermal) -> bool {
+    ui.label(format!(
+        "Temperature: {:.0} K, impacts {:.3} W",
+        thermal.temperature, thermal.impact_power
+    ));
+    let sliders = [
+        ui.add(
+            egui::Slider::new(&mut thermal.heat_capacity, 0.001..=100.0)
+                .logarithmic(true)
+                .text("Heat capacity, J/K"),
+        ),
+        ui.add(egui::Slider::new(&mut thermal.emissivity, 0.01..=1.0).text("Emissivity")),
+    ];
+    sliders.iter().any(|s| s.dragged())
+}
+
+/// Light source and surface of a photocathode, returns whether a slider is dragged.
+fn light_source_controls(
+    ui: &mut egui::Ui,
+    photocathode: &mut Photocathode,
+    gap: Option<f32>,
+) -> bool {
+    egui::ComboBox::from_label("Surface")
+        .selected_text(photocathode.material.name())
+        .show_ui(ui, |ui| {
+            for option in CathodeMaterial::ALL {
+                ui.selectable_value(&mut photocathode.material, option, option.name());
+            }
+        });
+    let sliders = [
+        ui.add(
+            egui::Slider::new(&mut photocathode.wavelength, 150.0..=800.0)
+                .text("Wavelength, nm"),
+        ),
+        ui.add(
+            egui::Slider::new(&mut photocathode.photon_rate, 0.0..=2000.0)
+                .text("Photons per second"),
+        ),
+        ui.add(
+            egui::Slider::new(&mut photocathode.quantum_efficiency, 0.0..=1.0)
+                .text("Quantum efficiency"),
+        ),
+    ];
+    ui.label(format!(
+        "hν = {:.2} eV, φ = {:.2} eV",
+        photocathode.photon_energy(),
+        photocathode.material.work_function()
+    ));
+    let kinetic_energy = photocathode.kinetic_energy();
+    if kinetic_energy > 0.0 {
+        ui.label(format!("Photoelectrons at {kinetic_energy:.2} eV"));
+        // the voltage slider sets the acceleration, which stops them within `gap` at
+        // `KE / (EV_PER_ENERGY_UNIT gap)` and below
+        match gap {
+            Some(gap) => ui.label(format!(
+                "Stopped within the {gap:.1} gap at Voltage ≤ {:.2}",
+                -kinetic_energy / (EV_PER_ENERGY_UNIT * gap)
+            )),
+            None => ui.label(format!("Stopped by a {kinetic_energy:.2} V retarding potential")),
+        };
+    } else {
+        ui.label("Below threshold, no photoelectrons");
+    }
+    sliders.iter().any(|s| s.dragged())
+}
+
+/// Distance from the front face of a plate to the next plate in front of it.
+fn gap_in_front(entity: Entity, plates: &[(Entity, Transform, Vec3)]) -> Option<f32> {
+    let (_, transform, half_size) = plates.iter().find(|(other, ..)| *other == entity)?;
+    let normal = transform.rotation * Vec3::Z;
+    let face = transform.translation + normal * half_size.z;
+    plates
+        .iter()
+        .filter(|(other, ..)| *other != entity)
+        .filter_map(|(_, transform, half_size)| {
+            ray_box_distance(face, normal, transform, *half_size)
+        })
+        .filter(|gap| *gap > 0.0)
+        .min_by(f32::total_cmp)
+}
+
+/// Work function and apex of a field emitter, returns whether a slider is dragged.
+fn field_emitter_controls(ui: &mut egui::Ui, emitter: &mut FieldEmitter) -> bool {
+    let sliders = [
+        ui.add(
+            egui::Slider::new(&mut emitter.work_function, 1.0..=7.0).text("Work function, eV"),
+        ),
+        ui.add(
+            egui::Slider::new(&mut emitter.enhancement, 1.0..=10000000.0)
+                .logarithmic(true)
+                .text("Field enhancement"),
+        ),
+        ui.add(
+            egui::Slider::new(&mut emitter.area, 0.01..=100.0)
+                .logarithmic(true)
+                .text("Emitting area, nm²"),
+        ),
+    ];
+    ui.label(format!("Apex field: {:.2} V/nm", emitter.surface_field * 1e-9));
+    ui.label(format!("Emission: {:.3e} e/s", emitter.emission_rate));
+    sliders.iter().any(|s| s.dragged())
+}
+
+/// Selects the electrode under the cursor on right click.
+#[allow(clippy::too_many_arguments)]
+pub fn pick_electrode(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<bevy::window::PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    plates: Query<(Entity, &Transform, &Plate), With<Electrode>>,
+    cylinders: Query<(Entity, &Transform, &Cylinder), With<Electrode>>,
+    tips: Query<(Entity, &Transform, &Tip), With<Electrode>>,
+    ui_state: Res<UiState>,
+    mut selected: ResMut<SelectedElectrode>,
+) {
+    if ui_state.is_window_focused || !mouse_buttons.just_pressed(MouseButton::Right) {
+        return;
+    }
+    let Some(cursor) = windows.get_single().ok().and_then(|w| w.cursor_position()) else {
+        return;
+    };
+    let (camera, camera_transform) = camera.single();
+    let Some(ray) = camera.viewport_to_world(camera_transform, cursor) else {
+        return;
+    };
+    let direction = Vec3::from(ray.direction);
+
+    let plate_hits = plates.iter().filter_map(|(entity, transform, plate)| {
+        let half_size = Vec3::new(plate.width, plate.height, plate.depth) / 2.0;
+        ray_box_distance(ray.origin, direction, transform, half_size).map(|t| (entity, t))
+    });
+    let cylinder_hits = cylinders.iter().filter_map(|(entity, transform, cylinder)| {
+        ray_cylinder_distance(ray.origin, direction, transform, cylinder).map(|t| (entity, t))
+    });
+    let tip_hits = tips.iter().filter_map(|(entity, transform, tip)| {
+        let half_size = Vec3::new(tip.radius, tip.length / 2.0, tip.radius);
+        ray_box_distance(ray.origin, direction, transform, half_size).map(|t| (entity, t))
+    });
+
+    selected.0 = plate_hits
+        .chain(cylinder_hits)
+        .chain(tip_hits)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity);
+}
+
+fn ray_box_distance(origin: Vec3, direction: Vec3, transform: &Transform, half_size: Vec3) -> Option<f32> {
+    // slab test in the box's own frame
+    let inverse = transform.rotation.inverse();
+    let origin = inverse * (origin - transform.translation);
+    let direction = inverse * direction;
+
+    let t1 = (-half_size - origin) / direction;
+    let t2 = (half_size - origin) / direction;
+    let t_near = t1.min(t2).max_element();
+    let t_far = t1.max(t2).min_element();
+
+    if t_near > t_far || t_far < 0.0 {
+        return None;
+    }
+    Some(t_near.max(0.0))
+}
+
+fn ray_cylinder_distance(
+    origin: Vec3,
+    direction: Vec3,
+    transform: &Transform,
+    cylinder: &Cylinder,
+) -> Option<f32> {
+    // the cylinder axis is the local y axis
+    let inverse = transform.rotation.inverse();
+    let origin = inverse * (origin - transform.translation);
+    let direction = inverse * direction;
+
+    let a = direction.x * direction.x + direction.z * direction.z;
+    let b = 2.0 * (origin.x * direction.x + origin.z * direction.z);
+    let c = origin.x * origin.x + origin.z * origin.z - cylinder.outer_radius * cylinder.outer_radius;
+    let discriminant = b * b - 4.0 * a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return None;
+    }
+
+    let sqrt_discriminant = discriminant.sqrt();
+    [(-b - sqrt_discriminant) / (2.0 * a), (-b + sqrt_discriminant) / (2.0 * a)]
+        .into_iter()
+        .filter(|t| *t >= 0.0)
+        .find(|t| (origin.y + direction.y * t).abs() <= cylinder.height / 2.0)
+}
+
+/// Applies electrode colours and highlights the selected electrode.
+#[allow(clippy::type_complexity)]
+pub fn update_electrode_materials(
+    selected: Res<SelectedElectrode>,
+    electrodes: Query<(Entity, Ref<Electrode>, &Handle<StandardMaterial>, Option<&Thermal>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (entity, electrode, material, thermal) in electrodes.iter() {
+        let highlight = if selected.0 == Some(entity) {
+            constants::SELECTION_HIGHLIGHT
+        } else {
+            Color::BLACK
+        };
+        let glow = thermal.map_or(Color::BLACK, |thermal| incandescence(thermal.temperature));
+        let emissive = highlight + glow;
+        let glow_changed = materials.get(material).is_some_and(|m| m.emissive != emissive);
+        if !electrode.is_changed() && !selected.is_changed() && !glow_changed {
+            continue;
+        }
+        let Some(material) = materials.get_mut(material) else {
+            continue;
+        };
+        // a heatmap texture carries its own colours
+        material.base_color = if material.base_color_texture.is_some() {
+            Color::WHITE
+        } else {
+            electrode.color
+        };
+        material.emissive = emissive;
+    }
+}
+
 pub fn change_background_color(
     input: Res<ButtonInput<KeyCode>>,
     mut clear_color: ResMut<ClearColor>,
@@ -163,10 +1402,7 @@ pub fn change_diode_type(
     keyboard_input: Res<ButtonInput<KeyCode>>
 ){
     if keyboard_input.just_pressed(KeyCode::Digit1) {
-        match state.get() {
-            SelectedScene::CylindricalDiode => next_state.set(SelectedScene::PlateDiode),
-            SelectedScene::PlateDiode => next_state.set(SelectedScene::CylindricalDiode),
-        }
+        next_state.set(state.get().next());
     }
 }
 
@@ -0,0 +1,281 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+
+use crate::physics::fields::{ElectricSources, MagneticSources};
+use crate::structs::{FieldVisualization, SliceAxis, VectorFieldKind};
+
+pub mod impacts;
+pub mod isosurface;
+pub mod marching_cubes;
+
+/// Length of a single field line integration step.
+const LINE_STEP: f32 = 1.0;
+const LINE_MAX_STEPS: usize = 300;
+/// Field lines are stopped once they leave this cube around the origin.
+const LINE_BOUND: f32 = 150.0;
+/// Field lines, arrows and the heatmap are recomputed this often, not every frame.
+const REFRESH_SECONDS: f32 = 0.5;
+
+const E_LINE_COLOR: Color = Color::rgb(1.0, 0.5, 0.0);
+const B_LINE_COLOR: Color = Color::rgb(0.2, 0.4, 1.0);
+
+#[derive(Component)]
+struct PotentialHeatmap;
+
+#[derive(Resource)]
+struct FieldGeometry {
+    e_lines: Vec<Vec<Vec3>>,
+    b_lines: Vec<Vec<Vec3>>,
+    arrows: Vec<(Vec3, Vec3, Color)>,
+    refresh: Timer,
+}
+
+pub fn visualization_plugin(app: &mut App) {
+    app.init_resource::<FieldVisualization>()
+        .insert_resource(FieldGeometry {
+            e_lines: Vec::new(),
+            b_lines: Vec::new(),
+            arrows: Vec::new(),
+            refresh: Timer::from_seconds(REFRESH_SECONDS, TimerMode::Repeating),
+        })
+        .add_plugins((isosurface::isosurface_plugin, impacts::impacts_plugin))
+        .add_systems(Startup, setup_potential_heatmap)
+        .add_systems(
+            Update,
+            (
+                update_field_geometry,
+                draw_field_geometry.after(update_field_geometry),
+                update_potential_heatmap,
+            ),
+        );
+}
+
+/// Point on the slice plane, `u` and `v` run over `-1.0..=1.0`.
+pub fn slice_point(settings: &FieldVisualization, u: f32, v: f32) -> Vec3 {
+    let u = u * settings.extent;
+    let v = v * settings.extent;
+    match settings.slice_axis {
+        SliceAxis::X => Vec3::new(settings.slice_offset, u, v),
+        SliceAxis::Y => Vec3::new(u, settings.slice_offset, v),
+        SliceAxis::Z => Vec3::new(u, v, settings.slice_offset),
+    }
+}
+
+/// Evenly spaced `count` x `count` grid over the slice plane.
+fn slice_grid(settings: &FieldVisualization, count: usize) -> impl Iterator<Item = Vec3> + '_ {
+    let step = move |i: usize| {
+        if count > 1 {
+            i as f32 / (count - 1) as f32 * 2.0 - 1.0
+        } else {
+            0.0
+        }
+    };
+    (0..count).flat_map(move |i| (0..count).map(move |j| slice_point(settings, step(i), step(j))))
+}
+
+/// Blue-green-red colour ramp for `t` in `0.0..=1.0`.
+pub fn colormap(t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    Color::rgb(
+        (1.5 - (4.0 * t - 3.0).abs()).clamp(0.0, 1.0),
+        (1.5 - (4.0 * t - 2.0).abs()).clamp(0.0, 1.0),
+        (1.5 - (4.0 * t - 1.0).abs()).clamp(0.0, 1.0),
+    )
+}
+
+/// Follows the direction of `field` from `seed` both ways with midpoint (RK2) steps.
+fn trace_field_line(seed: Vec3, field: impl Fn(Vec3) -> Vec3) -> Vec<Vec3> {
+    let direction_at = |pos: Vec3, sign: f32| {
+        let f = field(pos);
+        if f.length_squared() < 1e-12 {
+            None
+        } else {
+            Some(sign * f.normalize())
+        }
+    };
+
+    let mut halves = [-1.0, 1.0].map(|sign| {
+        let mut points = vec![seed];
+        let mut pos = seed;
+        for _ in 0..LINE_MAX_STEPS {
+            let Some(k1) = direction_at(pos, sign) else {
+                break;
+            };
+            let Some(k2) = direction_at(pos + k1 * LINE_STEP / 2.0, sign) else {
+                break;
+            };
+            pos += k2 * LINE_STEP;
+            if pos.abs().max_element() > LINE_BOUND {
+                break;
+            }
+            points.push(pos);
+        }
+        points
+    });
+
+    let [backward, forward] = &mut halves;
+    backward.reverse();
+    backward.extend(forward.drain(1..));
+    std::mem::take(backward)
+}
+
+fn update_field_geometry(
+    time: Res<Time>,
+    settings: Res<FieldVisualization>,
+    mut geometry: ResMut<FieldGeometry>,
+    electric: ElectricSources,
+    magnetic: MagneticSources,
+) {
+    if !geometry.refresh.tick(time.delta()).just_finished() && !settings.is_changed() {
+        return;
+    }
+
+    geometry.e_lines = if settings.show_e_lines {
+        slice_grid(&settings, settings.seed_count)
+            .map(|seed| trace_field_line(seed, |p| electric.field_at(p)))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    geometry.b_lines = if settings.show_b_lines {
+        slice_grid(&settings, settings.seed_count)
+            .map(|seed| trace_field_line(seed, |p| magnetic.field_at(p)))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let samples = slice_grid(&settings, settings.arrow_count)
+        .map(|p| {
+            let value = match settings.arrows {
+                VectorFieldKind::None => Vec3::ZERO,
+                VectorFieldKind::Electric => electric.field_at(p),
+                VectorFieldKind::Magnetic => magnetic.field_at(p),
+            };
+            (p, value)
+        })
+        .collect::<Vec<_>>();
+    let max_magnitude = samples
+        .iter()
+        .map(|(_, v)| v.length())
+        .fold(0.0, f32::max);
+    let spacing = 2.0 * settings.extent / settings.arrow_count.max(2) as f32;
+    geometry.arrows = if settings.arrows == VectorFieldKind::None || max_magnitude == 0.0 {
+        Vec::new()
+    } else {
+        samples
+            .into_iter()
+            .filter(|(_, v)| v.length_squared() > 0.0)
+            .map(|(p, v)| {
+                let t = v.length() / max_magnitude;
+                (p, p + v.normalize() * spacing * 0.9 * t.sqrt(), colormap(t))
+            })
+            .collect()
+    };
+}
+
+fn draw_field_geometry(mut gizmos: Gizmos, geometry: Res<FieldGeometry>) {
+    for line in geometry.e_lines.iter() {
+        gizmos.linestrip(line.iter().copied(), E_LINE_COLOR);
+    }
+    for line in geometry.b_lines.iter() {
+        gizmos.linestrip(line.iter().copied(), B_LINE_COLOR);
+    }
+    for (start, end, color) in geometry.arrows.iter() {
+        gizmos.arrow(*start, *end, *color);
+    }
+}
+
+fn setup_potential_heatmap(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mesh = meshes.add(Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    ));
+    let material = materials.add(StandardMaterial {
+        base_color: Color::rgba(1.0, 1.0, 1.0, 0.7),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material,
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        PotentialHeatmap,
+    ));
+}
+
+fn update_potential_heatmap(
+    time: Res<Time>,
+    settings: Res<FieldVisualization>,
+    electric: ElectricSources,
+    mut refresh: Local<Option<Timer>>,
+    mut heatmap: Query<(&Handle<Mesh>, &mut Visibility), With<PotentialHeatmap>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Ok((mesh, mut visibility)) = heatmap.get_single_mut() else {
+        return;
+    };
+    *visibility = if settings.show_heatmap {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+
+    let refresh =
+        refresh.get_or_insert_with(|| Timer::from_seconds(REFRESH_SECONDS, TimerMode::Repeating));
+    if !settings.show_heatmap
+        || (!refresh.tick(time.delta()).just_finished() && !settings.is_changed())
+    {
+        return;
+    }
+
+    let n = settings.heatmap_resolution.max(2);
+    let positions = slice_grid(&settings, n).collect::<Vec<_>>();
+    let potentials = positions
+        .iter()
+        .map(|p| electric.potential_at(*p))
+        .collect::<Vec<_>>();
+    let min = potentials.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = potentials.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let range = (max - min).max(f32::EPSILON);
+
+    let colors = potentials
+        .iter()
+        .map(|v| colormap((v - min) / range).as_rgba_f32())
+        .collect::<Vec<_>>();
+    let normal = match settings.slice_axis {
+        SliceAxis::X => [1.0, 0.0, 0.0],
+        SliceAxis::Y => [0.0, 1.0, 0.0],
+        SliceAxis::Z => [0.0, 0.0, 1.0],
+    };
+    let mut indices = Vec::with_capacity((n - 1) * (n - 1) * 6);
+    for i in 0..n - 1 {
+        for j in 0..n - 1 {
+            let a = (i * n + j) as u32;
+            let b = a + n as u32;
+            indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+        }
+    }
+
+    let Some(mesh) = meshes.get_mut(mesh) else {
+        return;
+    };
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        positions.iter().map(|p| p.to_array()).collect::<Vec<_>>(),
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![normal; positions.len()]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.insert_indices(Indices::U32(indices));
+}
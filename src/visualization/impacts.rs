@@ -0,0 +1,184 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use super::colormap;
+use crate::structs::{Cylinder, Electrode, ElectrodeImpact, ImpactHeatmapSettings, Plate, Tip};
+
+/// Texels along each side of an impact heatmap.
+const RESOLUTION: usize = 64;
+
+/// Impacts on one electrode binned over its surface, laid out like the texture coordinates
+/// of its mesh: (x, y) as seen from the front face of plates, (φ, y) around the axis of
+/// cylinders and tips.
+#[derive(Component)]
+struct ImpactHeatmap {
+    /// absorbed weight per texel, row by row from the bottom edge up
+    counts: Vec<f32>,
+    image: Handle<Image>,
+    /// counts changed since the image was last painted
+    dirty: bool,
+}
+
+pub fn impacts_plugin(app: &mut App) {
+    app.init_resource::<ImpactHeatmapSettings>().add_systems(
+        Update,
+        (
+            attach_impact_heatmaps,
+            accumulate_impacts.after(attach_impact_heatmaps),
+            paint_impact_heatmaps.after(accumulate_impacts),
+            export_impact_heatmaps.after(paint_impact_heatmaps),
+        ),
+    );
+}
+
+fn attach_impact_heatmaps(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    electrodes: Query<Entity, Added<Electrode>>,
+) {
+    for entity in electrodes.iter() {
+        let image = images.add(Image::new_fill(
+            Extent3d {
+                width: RESOLUTION as u32,
+                height: RESOLUTION as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        ));
+        commands.entity(entity).try_insert(ImpactHeatmap {
+            counts: vec![0.0; RESOLUTION * RESOLUTION],
+            image,
+            dirty: true,
+        });
+    }
+}
+
+/// Texture coordinates of a point given in the electrode's own frame, both in `0.0..1.0`.
+fn surface_uv(
+    local: Vec3,
+    plate: Option<&Plate>,
+    cylinder: Option<&Cylinder>,
+    tip: Option<&Tip>,
+) -> Option<Vec2> {
+    if let Some(plate) = plate {
+        return Some(Vec2::new(local.x / plate.width + 0.5, local.y / plate.height + 0.5));
+    }
+    let height = cylinder.map(|c| c.height).or(tip.map(|t| t.length))?;
+    let phi = local.z.atan2(local.x).rem_euclid(TAU);
+    Some(Vec2::new(phi / TAU, local.y / height + 0.5))
+}
+
+#[allow(clippy::type_complexity)]
+fn accumulate_impacts(
+    mut settings: ResMut<ImpactHeatmapSettings>,
+    mut impacts: EventReader<ElectrodeImpact>,
+    mut heatmaps: Query<(
+        &Transform,
+        &mut ImpactHeatmap,
+        Option<&Plate>,
+        Option<&Cylinder>,
+        Option<&Tip>,
+    )>,
+) {
+    if settings.clear_requested {
+        settings.clear_requested = false;
+        for (_, mut heatmap, ..) in heatmaps.iter_mut() {
+            heatmap.counts.fill(0.0);
+            heatmap.dirty = true;
+        }
+    }
+
+    for impact in impacts.read() {
+        let Ok((transform, mut heatmap, plate, cylinder, tip)) = heatmaps.get_mut(impact.electrode)
+        else {
+            continue;
+        };
+        let local = transform.rotation.inverse() * (impact.position - transform.translation);
+        let Some(uv) = surface_uv(local, plate, cylinder, tip) else {
+            continue;
+        };
+        let texel = (uv * RESOLUTION as f32)
+            .floor()
+            .clamp(Vec2::ZERO, Vec2::splat((RESOLUTION - 1) as f32));
+        heatmap.counts[texel.y as usize * RESOLUTION + texel.x as usize] += impact.weight;
+        heatmap.dirty = true;
+    }
+}
+
+/// Repaints changed heatmaps and puts them on, or takes them off, the electrode materials.
+fn paint_impact_heatmaps(
+    settings: Res<ImpactHeatmapSettings>,
+    mut heatmaps: Query<(&mut ImpactHeatmap, &Electrode, &Handle<StandardMaterial>)>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (mut heatmap, electrode, material) in heatmaps.iter_mut() {
+        let texture = settings.enabled.then(|| heatmap.image.clone());
+        // only touch materials that need it, any mutable access re-uploads them
+        if materials.get(material).is_some_and(|m| m.base_color_texture != texture) {
+            let material = materials.get_mut(material).unwrap();
+            material.base_color = if settings.enabled {
+                Color::WHITE
+            } else {
+                electrode.color
+            };
+            material.base_color_texture = texture;
+        }
+
+        if !heatmap.dirty || !(settings.enabled || settings.export_requested) {
+            continue;
+        }
+        let Some(image) = images.get_mut(&heatmap.image) else {
+            continue;
+        };
+        let max = heatmap.counts.iter().copied().fold(0.0, f32::max);
+        for (texel, count) in image.data.chunks_exact_mut(4).zip(&heatmap.counts) {
+            let color = if *count > 0.0 {
+                colormap(count / max)
+            } else {
+                Color::BLACK
+            };
+            texel.copy_from_slice(&color.as_rgba_u8());
+        }
+        heatmap.dirty = false;
+    }
+}
+
+fn export_impact_heatmaps(
+    mut settings: ResMut<ImpactHeatmapSettings>,
+    heatmaps: Query<(&ImpactHeatmap, &Electrode)>,
+    images: Res<Assets<Image>>,
+) {
+    if !settings.export_requested {
+        return;
+    }
+    settings.export_requested = false;
+
+    for (heatmap, electrode) in heatmaps.iter() {
+        let Some(image) = images.get(&heatmap.image) else {
+            continue;
+        };
+        let name = electrode
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+            .collect::<String>();
+        let path = format!("impacts_{name}.png");
+        // texel rows run from the bottom edge up, image files from the top down
+        let saved = image
+            .clone()
+            .try_into_dynamic()
+            .map_err(|e| e.to_string())
+            .and_then(|image| image.flipv().save(&path).map_err(|e| e.to_string()));
+        match saved {
+            Ok(()) => info!("Saved impact heatmap of {} to {path}", electrode.name),
+            Err(e) => warn!("Could not save impact heatmap of {}: {e}", electrode.name),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+
+use super::colormap;
+use super::marching_cubes::{extract_isosurface, ScalarGrid};
+use crate::physics::fields::ElectricSources;
+use crate::structs::IsosurfaceSettings;
+
+/// The potential grid is resampled this often while isosurfaces are shown.
+const REFRESH_SECONDS: f32 = 1.0;
+
+#[derive(Component)]
+struct Isosurface;
+
+pub fn isosurface_plugin(app: &mut App) {
+    app.init_resource::<IsosurfaceSettings>()
+        .add_systems(Startup, setup_isosurface)
+        .add_systems(Update, update_isosurface);
+}
+
+fn setup_isosurface(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mesh = meshes.add(Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    ));
+    let material = materials.add(StandardMaterial {
+        alpha_mode: AlphaMode::Blend,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material,
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        Isosurface,
+    ));
+}
+
+fn update_isosurface(
+    time: Res<Time>,
+    mut settings: ResMut<IsosurfaceSettings>,
+    electric: ElectricSources,
+    mut refresh: Local<Option<Timer>>,
+    mut isosurface: Query<(&Handle<Mesh>, &mut Visibility), With<Isosurface>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Ok((mesh, mut visibility)) = isosurface.get_single_mut() else {
+        return;
+    };
+    *visibility = if settings.enabled {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+
+    let refresh =
+        refresh.get_or_insert_with(|| Timer::from_seconds(REFRESH_SECONDS, TimerMode::Repeating));
+    if !settings.enabled
+        || (!refresh.tick(time.delta()).just_finished() && !settings.is_changed())
+    {
+        return;
+    }
+
+    let n = settings.resolution.max(2);
+    let spacing = 2.0 * settings.extent / (n - 1) as f32;
+    let grid = ScalarGrid::sample(
+        UVec3::splat(n),
+        Vec3::splat(-settings.extent),
+        spacing,
+        |p| electric.potential_at(p),
+    );
+
+    let min = grid.values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = grid.values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    settings.bypass_change_detection().sampled_range = (min, max);
+    let range = (max - min).max(f32::EPSILON);
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut colors = Vec::new();
+    for level in settings.levels.iter().copied() {
+        let color = colormap((level - min) / range).with_a(settings.opacity);
+        for (triangle_positions, triangle_normals) in extract_isosurface(&grid, level) {
+            positions.extend(triangle_positions.map(|p| p.to_array()));
+            normals.extend(triangle_normals.map(|n| n.to_array()));
+            colors.extend([color.as_rgba_f32(); 3]);
+        }
+    }
+    let indices = (0..positions.len() as u32).collect::<Vec<_>>();
+
+    let Some(mesh) = meshes.get_mut(mesh) else {
+        return;
+    };
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.insert_indices(Indices::U32(indices));
+}
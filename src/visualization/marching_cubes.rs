@@ -0,0 +1,268 @@
+use std::sync::OnceLock;
+
+use bevy::prelude::*;
+
+/// Cube corners in the usual marching cubes order.
+const CORNERS: [UVec3; 8] = [
+    UVec3::new(0, 0, 0),
+    UVec3::new(1, 0, 0),
+    UVec3::new(1, 1, 0),
+    UVec3::new(0, 1, 0),
+    UVec3::new(0, 0, 1),
+    UVec3::new(1, 0, 1),
+    UVec3::new(1, 1, 1),
+    UVec3::new(0, 1, 1),
+];
+
+/// Corner pairs joined by each of the 12 cube edges.
+const EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (1, 2),
+    (2, 3),
+    (3, 0),
+    (4, 5),
+    (5, 6),
+    (6, 7),
+    (7, 4),
+    (0, 4),
+    (1, 5),
+    (2, 6),
+    (3, 7),
+];
+
+/// Corners of each cube face in cyclic order, with the edge following each corner.
+const FACES: [([usize; 4], [usize; 4]); 6] = [
+    ([0, 1, 2, 3], [0, 1, 2, 3]),
+    ([4, 5, 6, 7], [4, 5, 6, 7]),
+    ([0, 1, 5, 4], [0, 9, 4, 8]),
+    ([3, 2, 6, 7], [2, 10, 6, 11]),
+    ([0, 3, 7, 4], [3, 11, 7, 8]),
+    ([1, 2, 6, 5], [1, 10, 5, 9]),
+];
+
+/// Triangles (as edge indices) for each of the 256 corner sign configurations.
+///
+/// Rather than hard-coding the classic table it is derived once: every face contributes the
+/// segments separating its inside corners from the outside ones (ambiguous faces always
+/// cut off the inside corners, which keeps neighbouring cubes consistent), the segments
+/// are chained into closed loops and each loop is fanned into triangles.
+fn triangle_table() -> &'static [Vec<[usize; 3]>] {
+    static TABLE: OnceLock<Vec<Vec<[usize; 3]>>> = OnceLock::new();
+    TABLE.get_or_init(|| (0..256).map(triangulate_case).collect())
+}
+
+fn triangulate_case(case: usize) -> Vec<[usize; 3]> {
+    let inside = |corner: usize| case & (1 << corner) != 0;
+
+    // neighbours of each crossed edge along the surface
+    let mut links: [Vec<usize>; 12] = Default::default();
+    let mut link = |a: usize, b: usize| {
+        links[a].push(b);
+        links[b].push(a);
+    };
+    for (corners, edges) in FACES {
+        let crossed = (0..4)
+            .filter(|&i| inside(corners[i]) != inside(corners[(i + 1) % 4]))
+            .map(|i| edges[i])
+            .collect::<Vec<_>>();
+        match crossed.len() {
+            2 => link(crossed[0], crossed[1]),
+            4 => {
+                for i in (0..4).filter(|&i| inside(corners[i])) {
+                    link(edges[(i + 3) % 4], edges[i]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut triangles = Vec::new();
+    let mut visited = [false; 12];
+    for start in 0..12 {
+        if visited[start] || links[start].is_empty() {
+            continue;
+        }
+        let mut polygon = vec![start];
+        visited[start] = true;
+        let mut previous = start;
+        let mut current = links[start][0];
+        while current != start {
+            visited[current] = true;
+            polygon.push(current);
+            let next = if links[current][0] != previous {
+                links[current][0]
+            } else {
+                links[current][1]
+            };
+            previous = current;
+            current = next;
+        }
+        for i in 1..polygon.len() - 1 {
+            triangles.push([polygon[0], polygon[i], polygon[i + 1]]);
+        }
+    }
+    triangles
+}
+
+/// Scalar field sampled on a regular grid of `size` points per axis.
+pub struct ScalarGrid {
+    pub size: UVec3,
+    pub origin: Vec3,
+    pub spacing: f32,
+    pub values: Vec<f32>,
+}
+
+impl ScalarGrid {
+    pub fn sample(size: UVec3, origin: Vec3, spacing: f32, f: impl Fn(Vec3) -> f32) -> Self {
+        let mut values = Vec::with_capacity((size.x * size.y * size.z) as usize);
+        for z in 0..size.z {
+            for y in 0..size.y {
+                for x in 0..size.x {
+                    values.push(f(origin + UVec3::new(x, y, z).as_vec3() * spacing));
+                }
+            }
+        }
+        ScalarGrid {
+            size,
+            origin,
+            spacing,
+            values,
+        }
+    }
+
+    fn index(&self, p: UVec3) -> usize {
+        (p.x + self.size.x * (p.y + self.size.y * p.z)) as usize
+    }
+
+    fn value(&self, p: UVec3) -> f32 {
+        self.values[self.index(p)]
+    }
+
+    fn position(&self, p: UVec3) -> Vec3 {
+        self.origin + p.as_vec3() * self.spacing
+    }
+
+    /// Central difference gradient, one-sided at the grid boundary.
+    fn gradient(&self, p: UVec3) -> Vec3 {
+        let axis = |i: usize| {
+            let mut lo = p;
+            let mut hi = p;
+            if p[i] > 0 {
+                lo[i] -= 1;
+            }
+            if p[i] + 1 < self.size[i] {
+                hi[i] += 1;
+            }
+            let steps = (hi[i] - lo[i]).max(1) as f32;
+            (self.value(hi) - self.value(lo)) / (steps * self.spacing)
+        };
+        Vec3::new(axis(0), axis(1), axis(2))
+    }
+}
+
+/// Triangle soup of the `level` isosurface: positions with normals pointing up the gradient.
+pub fn extract_isosurface(grid: &ScalarGrid, level: f32) -> Vec<([Vec3; 3], [Vec3; 3])> {
+    let table = triangle_table();
+    let mut triangles = Vec::new();
+    if grid.size.min_element() < 2 {
+        return triangles;
+    }
+
+    for z in 0..grid.size.z - 1 {
+        for y in 0..grid.size.y - 1 {
+            for x in 0..grid.size.x - 1 {
+                let cell = UVec3::new(x, y, z);
+                let corners = CORNERS.map(|c| cell + c);
+                let values = corners.map(|c| grid.value(c));
+
+                let case = (0..8)
+                    .filter(|&i| values[i] < level)
+                    .fold(0, |case, i| case | (1 << i));
+                if case == 0 || case == 255 {
+                    continue;
+                }
+
+                let vertex = |edge: usize| {
+                    let (a, b) = EDGES[edge];
+                    let t = ((level - values[a]) / (values[b] - values[a])).clamp(0.0, 1.0);
+                    let position = grid
+                        .position(corners[a])
+                        .lerp(grid.position(corners[b]), t);
+                    let normal = grid
+                        .gradient(corners[a])
+                        .lerp(grid.gradient(corners[b]), t)
+                        .normalize_or_zero();
+                    (position, normal)
+                };
+
+                for [e0, e1, e2] in table[case].iter().copied() {
+                    let (p0, n0) = vertex(e0);
+                    let (mut p1, mut n1) = vertex(e1);
+                    let (mut p2, mut n2) = vertex(e2);
+                    // wind every triangle to face along the gradient
+                    if (p1 - p0).cross(p2 - p0).dot(n0 + n1 + n2) < 0.0 {
+                        std::mem::swap(&mut p1, &mut p2);
+                        std::mem::swap(&mut n1, &mut n2);
+                    }
+                    triangles.push(([p0, p1, p2], [n0, n1, n2]));
+                }
+            }
+        }
+    }
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashMap;
+
+    use super::*;
+
+    const RADIUS: f32 = 6.3;
+
+    fn sphere_grid() -> ScalarGrid {
+        ScalarGrid::sample(UVec3::splat(21), Vec3::splat(-10.0), 1.0, |p| p.length() - RADIUS)
+    }
+
+    #[test]
+    fn sphere_vertices_lie_on_the_level() {
+        let triangles = extract_isosurface(&sphere_grid(), 0.0);
+        assert!(!triangles.is_empty());
+        for (positions, normals) in triangles {
+            for (position, normal) in positions.into_iter().zip(normals) {
+                // linear interpolation of the distance cuts a chord inside the sphere
+                assert!((position.length() - RADIUS).abs() < 0.05, "{position}");
+                // up the gradient is out of the sphere
+                assert!(normal.dot(position.normalize()) > 0.9, "{normal} at {position}");
+            }
+        }
+    }
+
+    #[test]
+    fn sphere_mesh_is_closed() {
+        let key = |p: Vec3| (p * 1e3).round().as_ivec3().to_array();
+        let mut edges = HashMap::new();
+        for ([a, b, c], _) in extract_isosurface(&sphere_grid(), 0.0) {
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                *edges.entry((key(from), key(to))).or_insert(0) += 1;
+            }
+        }
+        // every edge is crossed once each way by the two consistently wound triangles on it
+        for ((from, to), count) in &edges {
+            assert_eq!(*count, 1, "edge {from:?} -> {to:?}");
+            assert_eq!(edges.get(&(*to, *from)), Some(&1), "open edge {from:?} -> {to:?}");
+        }
+    }
+
+    #[test]
+    fn no_surface_without_a_crossing() {
+        let size = UVec3::splat(6);
+        let inside = ScalarGrid::sample(size, Vec3::ZERO, 1.0, |_| -1.0);
+        assert!(extract_isosurface(&inside, 0.0).is_empty());
+        let outside = ScalarGrid::sample(size, Vec3::ZERO, 1.0, |_| 1.0);
+        assert!(extract_isosurface(&outside, 0.0).is_empty());
+        // a sphere well off the grid
+        let away = ScalarGrid::sample(size, Vec3::splat(50.0), 1.0, |p| p.length() - RADIUS);
+        assert!(extract_isosurface(&away, 0.0).is_empty());
+    }
+}